@@ -5,68 +5,95 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{ToTokens, quote};
 
 use crate::attrs::*;
-use crate::new_attr;
+use crate::{new_attr, gate_install_fn, gate_uninstall_fn, installer_entry};
 
-fn generate_install_fn(usr_fn_name: &syn::Ident, orig: &syn::Ident, bind_fn_name: &syn::Ident, attrs: &AcmdAttrs) -> syn::ItemFn {
+fn generate_install_fn(usr_fn_name: &syn::Ident, orig: &syn::Ident, bind_fn_names: &[syn::Ident], attrs: &AcmdAttrs) -> syn::ItemFn {
     let install_name = quote::format_ident!("{}_smashline_acmd_script_install", usr_fn_name);
     
     let agent = &attrs.agent;
 
-    let mut install_fn: syn::ItemFn = parse_quote! {
+    let category = &attrs.category;
+    let low_priority = &attrs.low_priority;
+
+    let scripts = attrs.scripts.iter().zip(bind_fn_names.iter()).enumerate();
+    let registrations = scripts.clone().map(|(idx, (script, bind_fn_name))| quote! {
+        registrations.push(unsafe { smashline::replace_acmd_script(agent, #script, Some(&mut #orig[#idx]), #category, #low_priority, #bind_fn_name as *const extern "C" fn()) }?);
+    });
+    // scripts are registered in order, so the first `registrations.len()` of them are the ones to take back
+    let rollbacks = scripts.map(|(idx, (script, bind_fn_name))| quote! {
+        if registrations.len() > #idx {
+            smashline::remove_acmd_script(agent, #script, #category, #bind_fn_name as *const extern "C" fn());
+            #orig[#idx] = 0 as _;
+        }
+    });
+
+    parse_quote! {
+        #[allow(non_snake_case)]
+        #[allow(unused_unsafe)]
+        pub fn #install_name() -> Result<Vec<smashline::AcmdRegistration>, smashline::InstallError> {
+            smashline::check_runtime(stringify!(#usr_fn_name))?;
+            let agent = unsafe { #agent };
+            let mut registrations = Vec::new();
+            let result: Result<(), smashline::InstallError> = (|| {
+                #(#registrations)*
+                Ok(())
+            })();
+            if let Err(err) = result {
+                unsafe {
+                    #(#rollbacks)*
+                }
+                return Err(err);
+            }
+            return Ok(registrations);
+        }
+    }
+}
+
+fn generate_uninstall_fn(usr_fn_name: &syn::Ident, orig: &syn::Ident, bind_fn_names: &[syn::Ident], attrs: &AcmdAttrs) -> syn::ItemFn {
+    let uninstall_name = quote::format_ident!("{}_smashline_acmd_script_uninstall", usr_fn_name);
+
+    let agent = &attrs.agent;
+
+    let mut uninstall_fn: syn::ItemFn = parse_quote! {
         #[allow(non_snake_case)]
         #[allow(unused_unsafe)]
-        pub fn #install_name() {
+        pub fn #uninstall_name() {
+            if !smashline::is_runtime_available() {
+                return;
+            }
             let agent = unsafe { #agent };
         }
     };
 
     let category = &attrs.category;
-    let low_priority = &attrs.low_priority;
 
-    let can_call_orig = attrs.scripts.len() == 1;
-
-    for script in attrs.scripts.iter() {
-        if can_call_orig {
-            install_fn.block.stmts.push(parse_quote! {
-                unsafe { smashline::replace_acmd_script(agent, #script, Some(&mut #orig), #category, #low_priority, #bind_fn_name as *const extern "C" fn()) };
-            })
-        } else {
-            install_fn.block.stmts.push(parse_quote! {
-                unsafe { smashline::replace_acmd_script(agent, #script, None, #category, #low_priority, #bind_fn_name as *const extern "C" fn()) };
-            })
-        }
+    for (idx, (script, bind_fn_name)) in attrs.scripts.iter().zip(bind_fn_names.iter()).enumerate() {
+        uninstall_fn.block.stmts.push(parse_quote! {
+            unsafe {
+                smashline::remove_acmd_script(agent, #script, #category, #bind_fn_name as *const extern "C" fn());
+                #orig[#idx] = 0 as _;
+            }
+        })
     }
 
-    install_fn
+    uninstall_fn
 }
 
-fn generate_original_macro(usr_fn_name: &syn::Ident, orig_name: &syn::Ident, valid: bool) -> syn::Stmt {
-    if valid {
-        parse_quote! {
-            macro_rules! original {
-                ($agent:ident) => {
-                    {
-                        #[allow(unused_unsafe)]
-                        if true {
-                            unsafe {
-                                if #orig_name.is_null() {
-                                    panic!("Error calling ACMD script {}, original function not in memory.", stringify!(#usr_fn_name));
-                                }
-                                std::mem::transmute::<_, extern "C" fn(&mut smash::lua2cpp::L2CAgentBase, *mut smash::lib::utility::Variadic)>(#orig_name)($agent, &0u64 as *const u64 as _);
+fn generate_original_macro(usr_fn_name: &syn::Ident, orig_arg: &syn::Ident) -> syn::Stmt {
+    parse_quote! {
+        macro_rules! original {
+            ($agent:ident) => {
+                {
+                    #[allow(unused_unsafe)]
+                    if true {
+                        unsafe {
+                            if #orig_arg.is_null() {
+                                panic!("Error calling ACMD script {}, original function not in memory.", stringify!(#usr_fn_name));
                             }
-                        } else {
-                            unreachable!()
+                            std::mem::transmute::<_, extern "C" fn(&mut smash::lua2cpp::L2CAgentBase, *mut smash::lib::utility::Variadic)>(#orig_arg)($agent, &0u64 as *const u64 as _);
                         }
-                    }
-                }
-            }
-        }
-    } else {
-        parse_quote! {
-            macro_rules! original {
-                ($agent:ident) => {
-                    {
-                        compile_error!("ACMD replacements which replace multiple scripts cannot call `original!`")
+                    } else {
+                        unreachable!()
                     }
                 }
             }
@@ -78,7 +105,15 @@ pub fn install_acmd_script(input: TokenStream) -> TokenStream {
     let usr_fn_name = parse_macro_input!(input as syn::Ident);
     let install_name = quote::format_ident!("{}_smashline_acmd_script_install", usr_fn_name);
     quote!(
-        unsafe { #install_name(); }
+        unsafe { #install_name() }
+    ).into()
+}
+
+pub fn uninstall_acmd_script(input: TokenStream) -> TokenStream {
+    let usr_fn_name = parse_macro_input!(input as syn::Ident);
+    let uninstall_name = quote::format_ident!("{}_smashline_acmd_script_uninstall", usr_fn_name);
+    quote!(
+        unsafe { #uninstall_name(); }
     ).into()
 }
 
@@ -89,37 +124,59 @@ pub fn acmd_script(attr: TokenStream, input: TokenStream) -> TokenStream {
     let usr_fn_name = usr_fn.sig.ident.clone();
 
     let usr_new_name = quote::format_ident!("{}_smashline_acmd_script_usr", usr_fn_name);
-    let bind_fn_name = quote::format_ident!("{}_smashline_acmd_script_bind", usr_fn_name);
     let orig_name = quote::format_ident!("{}_smashline_acmd_script_orig", usr_fn_name);
+    let orig_arg = quote::format_ident!("_smashline_acmd_script_orig");
 
-    usr_fn.sig.ident = usr_new_name.clone();;
+    // one bind function per script, each forwarding the original of the script it was registered for
+    let bind_fn_names: Vec<syn::Ident> = (0..attrs.scripts.len()).map(|idx| {
+        quote::format_ident!("{}_smashline_acmd_script_bind_{}", usr_fn_name, idx)
+    }).collect();
+    let script_count = attrs.scripts.len();
+
+    usr_fn.sig.ident = usr_new_name.clone();
+    usr_fn.sig.inputs.push(parse_quote! { #orig_arg: *const extern "C" fn() });
     usr_fn.attrs.push(
         new_attr("inline", Some("(always)"))
     );
 
-    let orig_macro = generate_original_macro(&usr_fn_name, &orig_name, attrs.scripts.len() == 1);
+    let orig_macro = generate_original_macro(&usr_fn_name, &orig_arg);
 
     usr_fn.block.stmts.insert(0, orig_macro);
 
-    let install_fn = generate_install_fn(&usr_fn_name, &orig_name, &bind_fn_name, &attrs);
+    let install_fn = gate_install_fn(generate_install_fn(&usr_fn_name, &orig_name, &bind_fn_names, &attrs), &attrs.enabled_if, &usr_fn_name);
+    let uninstall_fn = gate_uninstall_fn(generate_uninstall_fn(&usr_fn_name, &orig_name, &bind_fn_names, &attrs), &attrs.enabled_if, &usr_fn_name);
+    let installer = installer_entry(&usr_fn_name, "acmd_script", attrs.manual);
+
+    let bind_fns = bind_fn_names.iter().enumerate().map(|(idx, bind_fn_name)| {
+        quote!(
+            #[allow(non_snake_case)]
+            #[inline(never)]
+            unsafe extern "C" fn #bind_fn_name(agent: &mut smash::lua2cpp::L2CAgentBase, _: &mut smash::lib::utility::Variadic) {
+                #usr_new_name(agent, #orig_name[#idx]);
+                #[cfg(target_arch = "aarch64")]
+                asm!(r#"
+                b #0x8
+                .byte 0xE5, 0xB1, 0x00, 0xB0
+                "#);
+            }
+        )
+    });
 
     quote!(
         #[allow(non_snake_case)]
         #[allow(non_upper_case_globals)]
-        static mut #orig_name: *const extern "C" fn() = 0 as _;
+        static mut #orig_name: [*const extern "C" fn(); #script_count] = [0 as _; #script_count];
 
         #usr_fn
 
         #install_fn
 
-        #[allow(non_snake_case)]
-        #[inline(never)]
-        unsafe extern "C" fn #bind_fn_name(agent: &mut smash::lua2cpp::L2CAgentBase, _: &mut smash::lib::utility::Variadic) {
-            #usr_new_name(agent);
-            asm!(r#"
-            b #0x8
-            .byte 0xE5, 0xB1, 0x00, 0xB0
-            "#)
-        }
+        #uninstall_fn
+
+        #installer
+
+        #(
+            #bind_fns
+        )*
     ).into()
 }
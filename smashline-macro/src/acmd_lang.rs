@@ -8,8 +8,11 @@ use syn::punctuated::Punctuated;
 use quote::{quote, ToTokens, TokenStreamExt};
 use proc_macro2::TokenStream as TokenStream2;
 
+use crate::acmd_signatures::{self, Param};
+
 #[derive(Debug)]
 struct AcmdFuncCall {
+    pub unchecked: Option<kw::unchecked>,
     pub name: Path,
     pub paren_token: syn::token::Paren,
     pub args: Punctuated<ArgExpr, Token![,]>,
@@ -44,6 +47,11 @@ impl Parse for AcmdFuncCall {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let content;
         Ok(Self {
+            unchecked: if input.peek(kw::unchecked) && !input.peek2(token::Paren) {
+                Some(input.parse()?)
+            } else {
+                None
+            },
             name: input.parse()?,
             paren_token: syn::parenthesized!(content in input),
             args: content.parse_terminated(ArgExpr::parse)?,
@@ -56,6 +64,211 @@ impl Parse for AcmdFuncCall {
     }
 }
 
+// orders a call's arguments by `params`, positional ones first and then the named ones, filling in defaults
+//
+// a call without named arguments has to give every argument, otherwise dropping one in the middle would shift the
+// rest into the wrong parameters and still compile with a default filled in at the end
+fn resolve_args(name: &str, params: &[Param], func_call: &AcmdFuncCall) -> syn::Result<Vec<TokenStream2>> {
+    let mut values: Vec<Option<TokenStream2>> = vec![None; params.len()];
+    let mut named = false;
+
+    if func_call.args.len() > params.len() {
+        return Err(arity_error(name, params.len(), func_call, ""));
+    }
+
+    for (idx, arg) in func_call.args.iter().enumerate() {
+        let arg_name = match &arg.name {
+            Some(arg_name) => arg_name,
+            None if named => return Err(syn::Error::new_spanned(&arg.expr, "Positional arguments have to come before named ones.")),
+            None => {
+                values[idx] = Some(arg.expr.to_token_stream());
+                continue;
+            }
+        };
+        named = true;
+
+        let idx = match params.iter().position(|(param, _)| arg_name == param) {
+            Some(idx) => idx,
+            None => return Err(syn::Error::new(arg_name.span(), format!(
+                "{} has no parameter `{}`, expected one of {}", name, arg_name,
+                params.iter().map(|(param, _)| *param).collect::<Vec<_>>().join(", ")
+            )))
+        };
+        if values[idx].is_some() {
+            return Err(syn::Error::new(arg_name.span(), format!("`{}` is given more than once", arg_name)));
+        }
+        values[idx] = Some(arg.expr.to_token_stream());
+    }
+
+    if !named && func_call.args.len() != params.len() {
+        return Err(arity_error(name, params.len(), func_call, ""));
+    }
+
+    values.into_iter().zip(params.iter()).map(|(value, &(param, default))| match (value, default) {
+        (Some(value), _) => Ok(value),
+        (None, Some(default)) => Ok(default.parse().unwrap()),
+        (None, None) => Err(syn::Error::new(func_call.paren_token.span, format!(
+            "{} is missing its `{}` argument", name, param
+        )))
+    }).collect()
+}
+
+fn arity_error(name: &str, expected: usize, func_call: &AcmdFuncCall, note: &str) -> syn::Error {
+    syn::Error::new(func_call.paren_token.span, format!(
+        "{} expects {} argument{}, got {}{}", name, expected, if expected == 1 { "" } else { "s" }, func_call.args.len(), note
+    ))
+}
+
+// checks a call against the signature of the function it calls, or only its number of arguments for the functions in
+// `acmd_signatures::ARITIES`. Functions acmd! knows neither for are passed through as written
+fn checked_args(name: &str, func_call: &AcmdFuncCall) -> syn::Result<Vec<TokenStream2>> {
+    if func_call.unchecked.is_none() {
+        if let Some(signature) = acmd_signatures::find(name) {
+            return resolve_args(signature.name, signature.params, func_call);
+        }
+        if let Some(arity) = acmd_signatures::arity(name) {
+            if arity != func_call.args.len() {
+                return Err(arity_error(name, arity, func_call, ", prefix the call with `unchecked` if this is intended"));
+            }
+        }
+    }
+    Ok(func_call.args.iter().map(|arg| arg.expr.to_token_stream()).collect())
+}
+
+// the path of an `sv_*` function relative to `smash::app`, however much of `smash::app` the call spells out
+fn sv_function_path(path: &Path) -> Option<Vec<&syn::PathSegment>> {
+    let position = path.segments.iter().position(|segment| segment.ident.to_string().starts_with("sv_"))?;
+    let prefix: Vec<String> = path.segments.iter().take(position).map(|segment| segment.ident.to_string()).collect();
+    match prefix.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] | ["app"] | ["smash", "app"] => Some(path.segments.iter().skip(position).collect()),
+        _ => None
+    }
+}
+
+// FT_MOTION_RATE takes how many frames one frame of the motion lasts, so 2.0 plays it at half speed. The other two
+// are written in terms of it:
+//  - FT_MOTION_RATE_RANGE(start, end, frames) sets the rate that makes motion frames `start` to `end` last `frames` frames
+//  - FT_DESIRED_RATE(motion_frames, frames) sets the rate that makes `motion_frames` motion frames last `frames` frames
+// both only set the rate, like FT_MOTION_RATE it stays in effect until the script changes it again, e.g. with
+// FT_MOTION_RATE(1) at motion frame `end`.
+// frame() and wait() always count motion frames, so the frame numbers after a rate change still match the animation
+// and only the time between them changes.
+fn motion_rate(func_call: &AcmdFuncCall) -> Option<TokenStream2> {
+    let name = func_call.name.get_ident()?.to_string();
+    let (params, rate): (&[Param], _) = match name.as_str() {
+        "FT_MOTION_RATE" => (&[("FSM", None)], quote!(fsm)),
+        "FT_MOTION_RATE_RANGE" => (&[("start", None), ("end", None), ("frames", None)], quote!(frames / (end - start))),
+        "FT_DESIRED_RATE" => (&[("motion_frames", None), ("frames", None)], quote!(frames / motion_frames)),
+        _ => return None
+    };
+
+    let args = match resolve_args(&name, params, func_call) {
+        Ok(args) => args,
+        Err(err) => return Some(err.to_compile_error())
+    };
+    // the rate would be infinite or NaN, which can only be caught here when both are written out
+    if name == "FT_MOTION_RATE_RANGE" {
+        if let (Some(start), Some(end)) = (literal_value(&args[0]), literal_value(&args[1])) {
+            if end - start == 0.0 {
+                return Some(syn::Error::new_spanned(&args[1], "FT_MOTION_RATE_RANGE needs `end` to be a different frame than `start`").to_compile_error());
+            }
+        }
+    }
+    let params = params.iter().map(|(param, _)| Ident::new(&param.to_lowercase(), proc_macro2::Span::call_site()));
+    Some(quote!(
+        {
+            let (#(#params,)*) = (#((#args) as f32,)*);
+            l2c_agent.clear_lua_stack();
+            l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_num(#rate));
+            ::smash::app::sv_animcmd::FT_MOTION_RATE(lua_state);
+        }
+    ))
+}
+
+fn literal_value(tokens: &TokenStream2) -> Option<f64> {
+    match syn::parse2(tokens.clone()).ok()? {
+        syn::Lit::Int(lit) => lit.base10_parse().ok(),
+        syn::Lit::Float(lit) => lit.base10_parse().ok(),
+        _ => None
+    }
+}
+
+// vanilla scripts call a few "common" functions which aren't in sv_animcmd, these get expanded in place instead
+struct AcmdHelper {
+    name: &'static str,
+    // bound as locals the body can use
+    params: &'static [Param],
+    // written in the acmd! language
+    body: &'static str
+}
+
+const ACMD_HELPERS: &[AcmdHelper] = &[
+    AcmdHelper {
+        name: "game_CaptureCutCommon",
+        params: &[],
+        body: r#"{
+            ATTACK_ABS(
+                *::smash::lib::lua_const::FIGHTER_ATTACK_ABSOLUTE_KIND_CATCH, 0, 3.0, 100, 0, 60, 0.0, 1.0,
+                *::smash::lib::lua_const::ATTACK_LR_CHECK_F, 0.0, true, ::smash::hash40("collision_attr_normal"),
+                *::smash::lib::lua_const::ATTACK_SOUND_LEVEL_S, *::smash::lib::lua_const::COLLISION_SOUND_ATTR_KICK,
+                *::smash::lib::lua_const::ATTACK_REGION_NONE
+            );
+        }"#
+    }
+];
+
+fn expand_helper(func_call: &AcmdFuncCall) -> Option<TokenStream2> {
+    let name = func_call.name.get_ident()?;
+    let helper = ACMD_HELPERS.iter().find(|helper| name == helper.name)?;
+
+    let values = match resolve_args(helper.name, helper.params, func_call) {
+        Ok(values) => values,
+        Err(err) => return Some(err.to_compile_error())
+    };
+    let params = helper.params.iter().map(|(param, _)| Ident::new(param, proc_macro2::Span::call_site()));
+    let body: AcmdBlock = syn::parse_str(helper.body).unwrap();
+
+    Some(quote!(
+        {
+            let (#(#params,)*) = (#(#values,)*);
+            #body
+        }
+    ))
+}
+
+// helpers defined with #[acmd_helper] are called as `helper::name(...)`, the path a call has to spell out so acmd!
+// never mistakes one of them for an sv_animcmd function or the other way around
+fn user_helper(func_call: &AcmdFuncCall) -> Option<TokenStream2> {
+    let mut segments = func_call.name.segments.iter();
+    if func_call.name.leading_colon.is_some() || segments.next()?.ident != "helper" || segments.len() == 0 {
+        return None;
+    }
+
+    let helper = segments;
+    let args = func_call.args.iter().map(|arg| &arg.expr);
+    Some(quote!(
+        #(#helper)::*(lua_state, #(#args),*);
+    ))
+}
+
+pub fn acmd_helper(input: TokenStream) -> TokenStream {
+    let mut helper = parse_macro_input!(input as syn::ItemFn);
+
+    // called from acmd! with its lua_state, the body can use acmd! without passing one
+    helper.sig.inputs.insert(0, parse_quote!(lua_state: u64));
+    let block = &helper.block;
+    helper.block = parse_quote!({
+        #[allow(unused_variables)]
+        let l2c_agent = &mut ::smash::lib::L2CAgent::new(lua_state);
+        #[allow(unused_variables)]
+        let module_accessor = ::smash::app::sv_system::battle_object_module_accessor(lua_state);
+        #block
+    });
+    helper.attrs.push(parse_quote!(#[allow(non_snake_case)]));
+
+    helper.into_token_stream().into()
+}
+
 fn single_acmd_func(func_call: &AcmdFuncCall) -> TokenStream2 {
     if func_call.name.is_ident("frame") {
         // frame
@@ -72,44 +285,23 @@ fn single_acmd_func(func_call: &AcmdFuncCall) -> TokenStream2 {
     } else if func_call.name.get_ident().is_some() {
         // ACMD functions
         let func_name = &func_call.name;
-        let args = func_call.args.iter().map(|arg| arg.expr.clone());
 
-        /* if func_name.to_token_stream().to_string() == "FT_MOTION_RATE" {
-            return quote!(
-                if current_frame >= target_frame {
-                    ::smash::app::lua_bind::MotionModule::set_rate(
-                        module_accessor,
-                        #(
-                            (1.0 / #args as f32).into()
-                        ),*
-                    );
-                }
-            );
-        }
-        else */ if func_name.to_token_stream().to_string() == "game_CaptureCutCommon" {
-            return quote!(
-                if current_frame >= target_frame {
-                    l2c_agent.clear_lua_stack();
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(*::smash::lib::lua_const::FIGHTER_ATTACK_ABSOLUTE_KIND_CATCH as u64));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(0 as u64));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_num(3.0));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(100 as u64));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(0 as u64));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(60 as u64));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_num(0.0));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_num(1.0));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(*::smash::lib::lua_const::ATTACK_LR_CHECK_F as u64));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_num(0.0));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_bool(true));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(::smash::hash40("collision_attr_normal")));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(*::smash::lib::lua_const::ATTACK_SOUND_LEVEL_S as u64));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(*::smash::lib::lua_const::COLLISION_SOUND_ATTR_KICK as u64));
-                    l2c_agent.push_lua_stack(&mut ::smash::lib::L2CValue::new_int(*::smash::lib::lua_const::ATTACK_REGION_NONE as u64));
-                    ::smash::app::sv_animcmd::ATTACK_ABS(lua_state);
-                }
-            );
+        if func_call.unchecked.is_none() {
+            if let Some(motion_rate) = motion_rate(func_call) {
+                return motion_rate;
+            }
+
+            if let Some(helper) = expand_helper(func_call) {
+                return helper;
+            }
         }
 
+        let name = func_name.to_token_stream().to_string();
+        let args = match checked_args(&name, func_call) {
+            Ok(args) => args,
+            Err(err) => return err.to_compile_error()
+        };
+
         quote!(
             l2c_agent.clear_lua_stack();
             #(
@@ -117,17 +309,26 @@ fn single_acmd_func(func_call: &AcmdFuncCall) -> TokenStream2 {
             )*
             ::smash::app::sv_animcmd::#func_name(lua_state);
         )
-    } else if func_call.name.segments.iter().next().unwrap().ident
-                    .to_string().starts_with("sv_") {
+    } else if let Some(helper) = user_helper(func_call) {
+        helper
+    } else if let Some(func_name) = sv_function_path(&func_call.name) {
         // Lua calling convention
-        let func_name = &func_call.name;
-        let args = func_call.args.iter().map(|arg| arg.expr.clone());
+        let segments: Vec<String> = func_name.iter().map(|segment| segment.ident.to_string()).collect();
+        // sv_animcmd functions are known by their name alone
+        let name = match segments.as_slice() {
+            [module, name] if module == "sv_animcmd" => name.clone(),
+            _ => segments.join("::")
+        };
+        let args = match checked_args(&name, func_call) {
+            Ok(args) => args,
+            Err(err) => return err.to_compile_error()
+        };
         quote!(
             l2c_agent.clear_lua_stack();
             #(
                 l2c_agent.push_lua_stack(&mut (#args).into());
             )*
-            ::smash::app::#func_name(lua_state);
+            ::smash::app::#(#func_name)::*(lua_state);
         )
     } else {
         // Module functions
@@ -166,6 +367,7 @@ pub fn generate_acmd_is_execute(input: TokenStream) -> TokenStream {
 mod kw {
     syn::custom_keyword!(rust);
     syn::custom_keyword!(Iterations);
+    syn::custom_keyword!(unchecked);
 }
 
 struct AcmdBlock {
@@ -201,7 +403,12 @@ struct AcmdIf {
     pub cond: Expr,
     pub block: AcmdBlock,
     pub else_token: Option<Token![else]>,
-    pub else_block: Option<AcmdBlock>,
+    pub else_branch: Option<AcmdElse>,
+}
+
+enum AcmdElse {
+    If(Box<AcmdIf>),
+    Block(AcmdBlock)
 }
 
 impl Parse for AcmdIf {
@@ -213,20 +420,63 @@ impl Parse for AcmdIf {
             cond: content.parse()?,
             block: input.parse()?,
             else_token: None,
-            else_block: None
+            else_branch: None
         };
 
         let lookahead = input.lookahead1();
 
         if lookahead.peek(Token![else]) {
             acmd_if.else_token = Some(input.parse()?);
-            acmd_if.else_block = Some(input.parse()?);
+            acmd_if.else_branch = Some(if input.peek(Token![if]) {
+                AcmdElse::If(Box::new(input.parse()?))
+            } else {
+                AcmdElse::Block(input.parse()?)
+            });
         }
 
         Ok(acmd_if)
     }
 }
 
+impl ToTokens for AcmdIf {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let cond = &self.cond;
+        let acmd_block = &self.block;
+        // an `else if` is generated as an `if` nested in the else block, so its condition is refreshed only when reached
+        let else_block = match &self.else_branch {
+            Some(AcmdElse::If(acmd_if)) => acmd_if.to_token_stream(),
+            Some(AcmdElse::Block(acmd_block)) => acmd_block.to_token_stream(),
+            None => TokenStream2::new()
+        };
+        tokens.append_all(quote!(
+            ::smashline::generate_acmd_is_execute!(#cond);
+            if #cond {
+                #acmd_block
+            }
+            else {
+                #else_block
+            }
+        ));
+    }
+}
+
+struct AcmdWhile {
+    pub cond: Expr,
+    pub block: AcmdBlock
+}
+
+impl Parse for AcmdWhile {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _: Token![while] = input.parse()?;
+        syn::parenthesized!(content in input);
+        Ok(Self {
+            cond: content.parse()?,
+            block: input.parse()?
+        })
+    }
+}
+
 struct AcmdFor {
     pub for_token: Token![for],
     pub parens: syn::token::Paren,
@@ -264,6 +514,7 @@ impl Parse for InlineRustBlock {
 
 enum AcmdStatement {
     If(AcmdIf),
+    While(AcmdWhile),
     For(AcmdFor),
     FuncCall(AcmdFuncCall),
     RustBlock(InlineRustBlock),
@@ -274,6 +525,8 @@ impl Parse for AcmdStatement {
         let lookahead = input.lookahead1();
         if lookahead.peek(Token![if]) {
             Ok(Self::If(input.parse()?))
+        } else if lookahead.peek(Token![while]) {
+            Ok(Self::While(input.parse()?))
         } else if lookahead.peek(Token![for]) {
             Ok(Self::For(input.parse()?))
         } else if lookahead.peek(kw::rust) {
@@ -288,18 +541,19 @@ impl ToTokens for AcmdStatement {
     fn to_tokens(&self, tokens: &mut TokenStream2) {
         let new_tokens = match self {
             Self::If(acmd_if) => {
-                let default_block = AcmdBlock { braces: syn::token::Brace::default(), statements: Vec::with_capacity(0) };
-                let cond = &acmd_if.cond;
-                let acmd_block = &acmd_if.block;
-                let else_block = &acmd_if.else_block.as_ref().unwrap_or(&default_block);
+                acmd_if.to_token_stream()
+            }
+            Self::While(acmd_while) => {
+                let cond = &acmd_while.cond;
+                let acmd_block = &acmd_while.block;
                 quote!(
-                    ::smashline::generate_acmd_is_execute!(#cond);
-                    if #cond {
+                    loop {
+                        ::smashline::generate_acmd_is_execute!(#cond);
+                        if !(#cond) {
+                            break;
+                        }
                         #acmd_block
                     }
-                    else {
-                        #else_block
-                    }
                 )
             }
             Self::For(acmd_for) => {
@@ -372,4 +626,115 @@ pub fn acmd(input: TokenStream) -> TokenStream {
             )*
         }
     ).into()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(call: &str) -> AcmdFuncCall {
+        syn::parse_str(call).unwrap()
+    }
+
+    fn resolve(signature: &str, func_call: &str) -> Result<Vec<String>, String> {
+        let signature = acmd_signatures::find(signature).unwrap();
+        resolve_args(signature.name, signature.params, &call(func_call))
+            .map(|args| args.iter().map(|arg| arg.to_string()).collect())
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn positional_calls_need_every_argument() {
+        assert_eq!(
+            resolve("EFFECT", "EFFECT(effect, bone, 0, 1, 2, 0, 0, 0, 1.5)"),
+            Err("EFFECT expects 16 arguments, got 9".to_owned())
+        );
+        assert_eq!(
+            resolve("EFFECT", "EFFECT(effect, bone, 0, 1, 2, 0, 0, 0, 1.5, 0, 0, 0, 0, 0, 0, true)").unwrap().len(),
+            16
+        );
+    }
+
+    #[test]
+    fn named_calls_fill_in_defaults() {
+        let args = resolve("EFFECT", "EFFECT(effect, bone, 0, 1, 2, 0, 0, 0, Terminate = true)").unwrap();
+        assert_eq!(args.len(), 16);
+        assert_eq!(args[8], "1.0");
+        assert_eq!(args[15], "true");
+
+        assert_eq!(
+            resolve("EFFECT", "EFFECT(effect, bone, 0, 1, 2, 0, 0, Size = 1.5)"),
+            Err("EFFECT is missing its `ZRot` argument".to_owned())
+        );
+        assert_eq!(
+            resolve("EFFECT", "EFFECT(effect, bone, 0, 1, 2, 0, 0, 0, Sise = 1.5)").unwrap_err(),
+            "EFFECT has no parameter `Sise`, expected one of Effect, Bone, X, Y, Z, XRot, YRot, ZRot, Size, RandX, RandY, RandZ, RandXRot, RandYRot, RandZRot, Terminate"
+        );
+    }
+
+    #[test]
+    fn signature_mismatches_are_errors() {
+        let args = "0, 0, hash40(\"top\"), 10.0, 361, 100, 0, 30, 5.0, 0.0, 8.0, 0.0, None, None, None, 1.0, 1.0, \
+            *ATTACK_SETOFF_KIND_ON, *ATTACK_LR_CHECK_F, false, 0, 0.0, 0, false, false, false, false, true, \
+            *COLLISION_SITUATION_MASK_GA, *COLLISION_CATEGORY_MASK_ALL, *COLLISION_PART_MASK_ALL, false, \
+            hash40(\"collision_attr_normal\"), *ATTACK_SOUND_LEVEL_M";
+        assert_eq!(
+            checked_args("ATTACK", &call(&format!("ATTACK({})", args))).unwrap_err().to_string(),
+            "ATTACK expects 36 arguments, got 34"
+        );
+        assert!(checked_args("ATTACK", &call(&format!("unchecked ATTACK({})", args))).is_ok());
+        assert_eq!(checked_args("NEW_FUNC", &call("NEW_FUNC(1, 2, 3)")).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn arity_mismatches_are_errors() {
+        assert_eq!(
+            checked_args("PLAY_SE", &call("PLAY_SE(1, 2)")).unwrap_err().to_string(),
+            "PLAY_SE expects 1 argument, got 2, prefix the call with `unchecked` if this is intended"
+        );
+        assert_eq!(
+            checked_args("sv_math::rand", &call("sv_math::rand(hash40(\"fighter\"))")).unwrap_err().to_string(),
+            "sv_math::rand expects 2 arguments, got 1, prefix the call with `unchecked` if this is intended"
+        );
+
+        assert_eq!(checked_args("PLAY_SE", &call("PLAY_SE(1)")).unwrap().len(), 1);
+        assert_eq!(checked_args("PLAY_SE", &call("unchecked PLAY_SE(1, 2)")).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn sv_functions_are_found_however_they_are_spelled() {
+        for path in &["sv_math::rand", "app::sv_math::rand", "smash::app::sv_math::rand", "::smash::app::sv_math::rand"] {
+            let path: Path = syn::parse_str(path).unwrap();
+            let segments: Vec<String> = sv_function_path(&path).unwrap().iter().map(|segment| segment.ident.to_string()).collect();
+            assert_eq!(segments, vec!["sv_math", "rand"]);
+        }
+        assert!(sv_function_path(&syn::parse_str("my_mod::sv_math::rand").unwrap()).is_none());
+        assert!(sv_function_path(&syn::parse_str("WorkModule::on_flag").unwrap()).is_none());
+    }
+
+    #[test]
+    fn motion_rate_ranges_need_distinct_frames() {
+        let error = motion_rate(&call("FT_MOTION_RATE_RANGE(5, 5.0, 10)")).unwrap().to_string();
+        assert!(error.contains("FT_MOTION_RATE_RANGE needs `end` to be a different frame than `start`"));
+        assert!(!motion_rate(&call("FT_MOTION_RATE_RANGE(5, 15, 10)")).unwrap().to_string().contains("compile_error"));
+        assert!(!motion_rate(&call("FT_MOTION_RATE_RANGE(start, start, 10)")).unwrap().to_string().contains("compile_error"));
+    }
+
+    #[test]
+    fn motion_rate_takes_fsm_like_decompiled_scripts() {
+        let named = motion_rate(&call("FT_MOTION_RATE(FSM=0.5)")).unwrap().to_string();
+        assert_eq!(named, motion_rate(&call("FT_MOTION_RATE(0.5)")).unwrap().to_string());
+        assert!(named.contains(&quote!(let (fsm,) = ((0.5) as f32,);).to_string()));
+        assert!(motion_rate(&call("FT_MOTION_RATE(rate=0.5)")).unwrap().to_string().contains("has no parameter `rate`, expected one of FSM"));
+    }
+
+    #[test]
+    fn only_helper_paths_are_helpers() {
+        assert_eq!(
+            user_helper(&call("helper::effect_MyLandingCommon(0.8)")).unwrap().to_string(),
+            quote!(effect_MyLandingCommon(lua_state, 0.8);).to_string()
+        );
+        assert!(user_helper(&call("effect_MyLandingCommon(0.8)")).is_none());
+        assert!(user_helper(&call("helper(0.8)")).is_none());
+    }
+}
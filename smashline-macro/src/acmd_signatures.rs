@@ -0,0 +1,176 @@
+// Parameter lists of the sv_animcmd functions acmd! checks calls against, named the way decompiled scripts name them,
+// e.g. `ATTACK(ID=0, Part=0, Bone=hash40("top"), ...)`. Calls prefixed with `unchecked` skip these checks.
+
+// a parameter's name and, if it can be left out, the expression passed instead
+pub type Param = (&'static str, Option<&'static str>);
+
+pub struct AcmdSignature {
+    pub name: &'static str,
+    pub params: &'static [Param]
+}
+
+const VOID: Option<&str> = Some("::smash::lib::L2CValue::new_void()");
+
+pub const SIGNATURES: &[AcmdSignature] = &[
+    AcmdSignature {
+        name: "ATTACK",
+        params: &[
+            ("ID", None),
+            ("Part", None),
+            ("Bone", None),
+            ("Damage", None),
+            ("Angle", None),
+            ("KBG", None),
+            ("FKB", None),
+            ("BKB", None),
+            ("Size", None),
+            ("X", None),
+            ("Y", None),
+            ("Z", None),
+            ("X2", VOID),
+            ("Y2", VOID),
+            ("Z2", VOID),
+            ("Hitlag", Some("1.0")),
+            ("SDI", Some("1.0")),
+            ("Clang_Rebound", Some("*::smash::lib::lua_const::ATTACK_SETOFF_KIND_ON")),
+            ("FacingRestrict", Some("*::smash::lib::lua_const::ATTACK_LR_CHECK_F")),
+            ("SetWeight", Some("false")),
+            ("ShieldDamage", Some("0")),
+            ("Trip", Some("0.0")),
+            ("Rehit", Some("0")),
+            ("Reflectable", Some("false")),
+            ("Absorbable", Some("false")),
+            ("Flinchless", Some("false")),
+            ("DisableHitlag", Some("false")),
+            ("Direct_Hitbox", Some("true")),
+            ("Ground_or_Air", Some("*::smash::lib::lua_const::COLLISION_SITUATION_MASK_GA")),
+            ("Hitbits", Some("*::smash::lib::lua_const::COLLISION_CATEGORY_MASK_ALL")),
+            ("CollisionPart", Some("*::smash::lib::lua_const::COLLISION_PART_MASK_ALL")),
+            ("FriendlyFire", Some("false")),
+            ("Effect", None),
+            ("SFXLevel", None),
+            ("SFXType", None),
+            ("Type", None)
+        ]
+    },
+    AcmdSignature {
+        name: "CATCH",
+        params: &[
+            ("ID", None),
+            ("Bone", None),
+            ("Size", None),
+            ("X", None),
+            ("Y", None),
+            ("Z", None),
+            ("X2", VOID),
+            ("Y2", VOID),
+            ("Z2", VOID),
+            ("Status", None),
+            ("Ground_or_Air", Some("*::smash::lib::lua_const::COLLISION_SITUATION_MASK_GA"))
+        ]
+    },
+    AcmdSignature {
+        name: "SEARCH",
+        params: &[
+            ("ID", None),
+            ("Part", None),
+            ("Bone", None),
+            ("Size", None),
+            ("X", None),
+            ("Y", None),
+            ("Z", None),
+            ("X2", VOID),
+            ("Y2", VOID),
+            ("Z2", VOID),
+            ("CollisionKind", None),
+            ("HitStatus", None),
+            ("Unk", None),
+            ("Ground_or_Air", Some("*::smash::lib::lua_const::COLLISION_SITUATION_MASK_GA")),
+            ("Hitbits", None),
+            ("CollisionPart", None),
+            ("Unk2", Some("false"))
+        ]
+    },
+    AcmdSignature {
+        name: "EFFECT",
+        params: &[
+            ("Effect", None),
+            ("Bone", None),
+            ("X", None),
+            ("Y", None),
+            ("Z", None),
+            ("XRot", None),
+            ("YRot", None),
+            ("ZRot", None),
+            ("Size", Some("1.0")),
+            ("RandX", Some("0")),
+            ("RandY", Some("0")),
+            ("RandZ", Some("0")),
+            ("RandXRot", Some("0")),
+            ("RandYRot", Some("0")),
+            ("RandZRot", Some("0")),
+            ("Terminate", Some("false"))
+        ]
+    },
+    AcmdSignature {
+        name: "EFFECT_FOLLOW",
+        params: &[
+            ("Effect", None),
+            ("Bone", None),
+            ("X", None),
+            ("Y", None),
+            ("Z", None),
+            ("XRot", None),
+            ("YRot", None),
+            ("ZRot", None),
+            ("Size", Some("1.0")),
+            ("Terminate", Some("false"))
+        ]
+    }
+];
+
+// functions only the number of arguments is checked for, sv_animcmd ones by name and the others by their path relative
+// to `smash::app`. The counts are the ones the vanilla scripts call these with, calls to overloads they never use
+// have to be marked `unchecked`
+pub const ARITIES: &[(&str, usize)] = &[
+    ("ATTACK_ABS", 15),
+    ("ATK_POWER", 2),
+    ("ATK_HIT_ABS", 5),
+    ("HIT_NODE", 2),
+    ("HIT_NO", 2),
+    ("WHOLE_HIT", 1),
+    ("REVERSE_LR", 0),
+    ("FT_START_CUTIN", 0),
+    ("SET_SPEED_EX", 3),
+    ("QUAKE", 1),
+    ("RUMBLE_HIT", 2),
+    ("LANDING_EFFECT", 16),
+    ("FOOT_EFFECT", 16),
+    ("EFFECT_FOLLOW_NO_STOP", 10),
+    ("EFFECT_FOLLOW_ALPHA", 11),
+    ("EFFECT_OFF_KIND", 3),
+    ("LAST_EFFECT_SET_RATE", 1),
+    ("LAST_EFFECT_SET_COLOR", 3),
+    ("LAST_EFFECT_SET_ALPHA", 1),
+    ("FLASH", 4),
+    ("FLASH_FRM", 5),
+    ("COL_NORMAL", 0),
+    ("BURN_COLOR", 4),
+    ("BURN_COLOR_FRAME", 5),
+    ("BURN_COLOR_NORMAL", 0),
+    ("PLAY_SE", 1),
+    ("PLAY_SE_REMAIN", 1),
+    ("PLAY_SEQUENCE", 1),
+    ("PLAY_STATUS", 1),
+    ("PLAY_LANDING_SE", 1),
+    ("STOP_SE", 1),
+    ("sv_math::rand", 2)
+];
+
+pub fn find(name: &str) -> Option<&'static AcmdSignature> {
+    SIGNATURES.iter().find(|signature| signature.name == name)
+}
+
+pub fn arity(name: &str) -> Option<usize> {
+    ARITIES.iter().find(|(function, _)| *function == name).map(|(_, arity)| *arity)
+}
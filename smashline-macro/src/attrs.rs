@@ -14,8 +14,21 @@ mod kw {
     syn::custom_keyword!(category);
     syn::custom_keyword!(low_priority);
     syn::custom_keyword!(status);
+    syn::custom_keyword!(statuses);
     syn::custom_keyword!(condition);
+    syn::custom_keyword!(conditions);
     syn::custom_keyword!(global);
+    syn::custom_keyword!(custom);
+    syn::custom_keyword!(raw);
+    syn::custom_keyword!(pattern);
+    syn::custom_keyword!(offset);
+    syn::custom_keyword!(priority);
+    syn::custom_keyword!(before);
+    syn::custom_keyword!(after);
+    syn::custom_keyword!(inline);
+    syn::custom_keyword!(enabled_if);
+    syn::custom_keyword!(per_match);
+    syn::custom_keyword!(manual);
 }
 
 // taken from skyline-rs hooking implementation
@@ -67,6 +80,67 @@ impl<Keyword: Parse, Item: Parse, Punctuation: Parse> Parse for BracketedList<Ke
     }
 }
 
+// `enabled_if = path::to::fn` or `enabled_if(path::to::fn, per_match)`, accepted by every attribute
+pub struct EnabledIf {
+    pub predicate: syn::Path,
+    pub per_match: bool
+}
+
+impl Parse for EnabledIf {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let predicate = input.parse()?;
+        let per_match = if input.peek(Token![,]) && input.peek2(kw::per_match) {
+            let _: Token![,] = input.parse()?;
+            let _: kw::per_match = input.parse()?;
+            true
+        } else {
+            false
+        };
+
+        Ok(Self {
+            predicate,
+            per_match
+        })
+    }
+}
+
+fn parse_enabled_if(input: ParseStream) -> syn::Result<EnabledIf> {
+    let MetaItem::<kw::enabled_if, EnabledIf> { item: enabled_if, .. } = input.parse()?;
+    Ok(enabled_if)
+}
+
+pub struct CallbackAttrs {
+    pub enabled_if: Option<EnabledIf>,
+    pub manual: bool
+}
+
+impl Parse for CallbackAttrs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut enabled_if = None;
+        let mut manual = false;
+        while !input.is_empty() {
+            if input.peek(kw::enabled_if) {
+                enabled_if = Some(parse_enabled_if(input)?);
+            } else if let Ok(_) = input.parse::<kw::manual>() {
+                manual = true;
+            } else {
+                return Err(input.error(format!(
+                    "Expected keyword '{}' or '{}' in macro declaration.", "enabled_if".bright_blue(), "manual".bright_blue()
+                )));
+            }
+
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+
+        Ok(Self {
+            enabled_if,
+            manual
+        })
+    }
+}
+
 pub enum HookModule {
     Lazy(syn::LitStr),
     Static(token::Static)
@@ -85,12 +159,37 @@ impl Parse for HookModule {
 
 pub enum HookSymbol {
     Resolved(syn::Path),
-    Unresolved(syn::LitStr)
+    Unresolved(syn::LitStr),
+    Pattern(syn::LitStr),
+    // no symbol, the hook's offset is relative to the start of the main module's text
+    Text
+}
+
+fn check_pattern(pattern: &syn::LitStr) -> syn::Result<()> {
+    let value = pattern.value();
+    if value.split_whitespace().next().is_none() {
+        return Err(syn::Error::new(pattern.span(), "Pattern cannot be empty."));
+    }
+
+    for byte in value.split_whitespace() {
+        let valid = byte == "?" || (byte.len() == 2 && byte.chars().all(|nibble| nibble == '?' || nibble.is_ascii_hexdigit()));
+        if !valid {
+            return Err(syn::Error::new(pattern.span(), format!(
+                "Invalid pattern byte '{}'. Expected two hex digits, where '{}' matches any nibble.", byte.bright_red(), "?".bright_blue()
+            )));
+        }
+    }
+
+    Ok(())
 }
 
 impl Parse for HookSymbol {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if let Ok(symbol) = input.parse::<syn::LitStr>() {
+        if input.peek(kw::pattern) && input.peek2(token::Paren) {
+            let MetaItem::<kw::pattern, syn::LitStr> { item: pattern, .. } = input.parse()?;
+            check_pattern(&pattern)?;
+            Ok(HookSymbol::Pattern(pattern))
+        } else if let Ok(symbol) = input.parse::<syn::LitStr>() {
             Ok(HookSymbol::Unresolved(symbol))
         } else {
             let symbol = input.parse()?;
@@ -101,7 +200,40 @@ impl Parse for HookSymbol {
 
 pub struct HookAttrs {
     pub module: HookModule,
-    pub symbol: HookSymbol
+    pub symbol: HookSymbol,
+    pub offset: Option<syn::LitInt>,
+    pub priority: Option<syn::Expr>,
+    pub before: Vec<syn::LitStr>,
+    pub after: Vec<syn::LitStr>,
+    pub inline: bool,
+    pub enabled_if: Option<EnabledIf>,
+    pub manual: bool
+}
+
+impl HookAttrs {
+    pub fn order(&self) -> TokenStream {
+        let priority = self.priority.as_ref().map(|priority| quote!(#priority)).unwrap_or(quote!(0));
+        let before = &self.before;
+        let after = &self.after;
+        quote!(
+            smashline::HookOrder {
+                priority: #priority,
+                before: &[#(#before),*],
+                after: &[#(#after),*]
+            }
+        )
+    }
+}
+
+// `before = "plugin"` or `before = ["plugin", ...]`
+fn parse_plugins<Keyword: Parse>(input: ParseStream) -> syn::Result<Vec<syn::LitStr>> {
+    if input.peek3(token::Bracket) {
+        let BracketedList::<Keyword, syn::LitStr, Token![,]> { list: plugins, .. } = input.parse()?;
+        Ok(plugins.into_iter().collect())
+    } else {
+        let MetaItem::<Keyword, syn::LitStr> { item: plugin, .. } = input.parse()?;
+        Ok(vec![plugin])
+    }
 }
 
 impl Parse for HookAttrs {
@@ -121,15 +253,89 @@ impl Parse for HookAttrs {
         let symbol = if input.peek(kw::symbol) {
             let MetaItem::<kw::symbol, HookSymbol> { item: symbol, .. } = input.parse()?;
             Ok(symbol)
+        } else if input.peek(kw::offset) {
+            Ok(HookSymbol::Text)
         } else {
             Err(input.error(format!(
-                "Expected keyword '{}' in macro declaration.", "symbol".bright_blue()
+                "Expected keyword '{}' or '{}' in macro declaration.", "symbol".bright_blue(), "offset".bright_blue()
             )))
         }?;
 
+        if input.peek(Token![,]) && input.peek2(kw::offset) {
+            let _: syn::Token![,] = input.parse()?;
+        }
+
+        let offset = if input.peek(kw::offset) {
+            let MetaItem::<kw::offset, syn::LitInt> { item: offset, .. } = input.parse()?;
+            offset.base10_parse::<u64>()?;
+            Some(offset)
+        } else {
+            None
+        };
+
+        let mut priority = None;
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        let mut inline = None;
+        let mut enabled_if = None;
+        let mut manual = false;
+        while !input.is_empty() {
+            let _: syn::Token![,] = input.parse()?;
+            if input.is_empty() {
+                break;
+            }
+
+            if input.peek(kw::priority) {
+                let MetaItem::<kw::priority, syn::Expr> { item, .. } = input.parse()?;
+                priority = Some(item);
+            } else if input.peek(kw::before) {
+                before.extend(parse_plugins::<kw::before>(input)?);
+            } else if input.peek(kw::after) {
+                after.extend(parse_plugins::<kw::after>(input)?);
+            } else if input.peek(kw::inline) {
+                inline = Some(input.parse::<kw::inline>()?);
+            } else if input.peek(kw::enabled_if) {
+                enabled_if = Some(parse_enabled_if(input)?);
+            } else if let Ok(_) = input.parse::<kw::manual>() {
+                manual = true;
+            } else {
+                return Err(input.error(format!(
+                    "Expected keyword '{}', '{}', '{}', '{}', '{}' or '{}' in macro declaration.", "priority".bright_blue(), "before".bright_blue(), "after".bright_blue(), "inline".bright_blue(), "enabled_if".bright_blue(), "manual".bright_blue()
+                )));
+            }
+        }
+
+        if let Some(inline) = &inline {
+            if let HookModule::Lazy(module) = &module {
+                return Err(syn::Error::new(module.span(), format!(
+                    "Inline hooks are placed in the main module and require '{}'.", "module = static".bright_blue()
+                )));
+            }
+            if priority.is_some() || !before.is_empty() || !after.is_empty() {
+                return Err(syn::Error::new(inline.span, "Inline hooks are not chained, so they cannot be ordered with priority, before or after."));
+            }
+        }
+
+        let relative = matches!(symbol, HookSymbol::Pattern(_) | HookSymbol::Text) || offset.is_some();
+        if let (HookModule::Lazy(module), true) = (&module, relative) {
+            return Err(syn::Error::new(module.span(), format!(
+                "Pattern and offset hooks are resolved against the main module and require '{}'.", "module = static".bright_blue()
+            )));
+        }
+        if let (HookSymbol::Unresolved(symbol), Some(_)) = (&symbol, &offset) {
+            return Err(syn::Error::new(symbol.span(), "Offsets cannot be applied to symbols resolved by the runtime, use a path or a pattern instead."));
+        }
+
         Ok(HookAttrs {
             module,
-            symbol
+            symbol,
+            offset,
+            priority,
+            before,
+            after,
+            inline: inline.is_some(),
+            enabled_if,
+            manual
         })
     }
 }
@@ -181,7 +387,9 @@ pub struct AcmdAttrs {
     pub agent: Hashable,
     pub scripts: Vec<Hashable>,
     pub category: syn::Path,
-    pub low_priority: syn::LitBool
+    pub low_priority: syn::LitBool,
+    pub enabled_if: Option<EnabledIf>,
+    pub manual: bool
 }
 
 impl Parse for AcmdAttrs {
@@ -223,23 +431,30 @@ impl Parse for AcmdAttrs {
             )))
         }?;
 
-        let low_priority = if let Ok(_) = input.parse::<Token![,]>() {
+        let mut low_priority = syn::LitBool::new(false, Span::call_site());
+        let mut enabled_if = None;
+        let mut manual = false;
+        while let Ok(_) = input.parse::<Token![,]>() {
             if let Ok(_) = input.parse::<kw::low_priority>() {
-                Ok(syn::LitBool::new(true, Span::call_site()))
+                low_priority = syn::LitBool::new(true, Span::call_site());
+            } else if input.peek(kw::enabled_if) {
+                enabled_if = Some(parse_enabled_if(input)?);
+            } else if let Ok(_) = input.parse::<kw::manual>() {
+                manual = true;
             } else {
-                Err(input.error(
+                return Err(input.error(
                     "Extra comma in macro declaration."
-                ))
+                ));
             }
-        } else {
-            Ok(syn::LitBool::new(false, Span::call_site()))
-        }?;
+        }
 
         Ok(Self {
             agent,
             scripts,
             category,
-            low_priority
+            low_priority,
+            enabled_if,
+            manual
         })
     }
 }
@@ -277,11 +492,127 @@ impl Parse for LuaConst {
     }
 }
 
+pub enum StatusKind {
+    Vanilla(LuaConst),
+    Custom(syn::LitStr)
+}
+
+impl StatusKind {
+    /// Custom status kinds are reserved per agent at install time, so they need the agent to be evaluated.
+    pub fn reserve(&self, agent: &Hashable) -> TokenStream {
+        match self {
+            StatusKind::Vanilla(lua_const) => lua_const.to_token_stream(),
+            StatusKind::Custom(name) => quote!(
+                smashline::LuaConstant::Evaluated(smashline::reserve_status_kind(#agent, #name))
+            )
+        }
+    }
+
+    /// The status kind an uninstall has to remove, `None` for a custom status kind that was never reserved.
+    pub fn lookup(&self, agent: &Hashable) -> TokenStream {
+        match self {
+            StatusKind::Vanilla(lua_const) => quote!(Some(#lua_const)),
+            StatusKind::Custom(name) => quote!(
+                smashline::get_status_kind(#agent, #name).map(smashline::LuaConstant::Evaluated)
+            )
+        }
+    }
+}
+
+impl Parse for StatusKind {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::custom) && input.peek2(token::Paren) {
+            let MetaItem::<kw::custom, syn::LitStr> { item: name, .. } = input.parse()?;
+            Ok(StatusKind::Custom(name))
+        } else {
+            Ok(StatusKind::Vanilla(input.parse()?))
+        }
+    }
+}
+
+/// Every `smashline::StatusCondition` variant alongside the `LUA_SCRIPT_STATUS_FUNC_*` constant it stands for
+/// and how many `&L2CValue` parameters its script takes after the agent.
+pub const STATUS_CONDITIONS: &[(&str, &str, usize)] = &[
+    ("Pre", "LUA_SCRIPT_STATUS_FUNC_STATUS_PRE", 0),
+    ("Main", "LUA_SCRIPT_STATUS_FUNC_STATUS_MAIN", 0),
+    ("End", "LUA_SCRIPT_STATUS_FUNC_STATUS_END", 0),
+    ("Init", "LUA_SCRIPT_STATUS_FUNC_INIT_STATUS", 0),
+    ("Exec", "LUA_SCRIPT_STATUS_FUNC_EXEC_STATUS", 0),
+    ("ExecStop", "LUA_SCRIPT_STATUS_FUNC_EXEC_STOP", 0),
+    ("Exit", "LUA_SCRIPT_STATUS_FUNC_EXIT_STATUS", 0),
+    ("MapCorrection", "LUA_SCRIPT_STATUS_FUNC_MAP_CORRECTION", 0),
+    ("FixCamera", "LUA_SCRIPT_STATUS_FUNC_FIX_CAMERA", 0),
+    ("FixPosSlow", "LUA_SCRIPT_STATUS_FUNC_FIX_POS_SLOW", 0),
+    ("CheckDamage", "LUA_SCRIPT_STATUS_FUNC_CHECK_DAMAGE", 1),
+    ("CheckAttack", "LUA_SCRIPT_STATUS_FUNC_CHECK_ATTACK", 2),
+    ("OnChangeLr", "LUA_SCRIPT_STATUS_FUNC_ON_CHANGE_LR", 2),
+    ("LeaveStop", "LUA_SCRIPT_STATUS_FUNC_LEAVE_STOP", 0),
+    ("NotifyEventGimmick", "LUA_SCRIPT_STATUS_FUNC_NOTIFY_EVENT_GIMMICK", 1),
+    ("CalcParam", "LUA_SCRIPT_STATUS_FUNC_CALC_PARAM", 0)
+];
+
+pub enum StatusCondition {
+    Known(syn::Ident),
+    Raw(LuaConst)
+}
+
+impl ToTokens for StatusCondition {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            StatusCondition::Known(variant) => {
+                quote!(
+                    smashline::LuaConstant::from(smashline::StatusCondition::#variant)
+                ).to_tokens(tokens)
+            },
+            StatusCondition::Raw(lua_const) => lua_const.to_tokens(tokens)
+        }
+    }
+}
+
+impl Parse for StatusCondition {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::raw) && input.peek2(token::Paren) {
+            let MetaItem::<kw::raw, LuaConst> { item: lua_const, .. } = input.parse()?;
+            return Ok(StatusCondition::Raw(lua_const));
+        }
+
+        let path = match input.parse::<syn::Expr>()? {
+            syn::Expr::Path(path) => path.path,
+            // integers and other computed values are explicit enough to pass through untouched
+            expr => return Ok(StatusCondition::Raw(LuaConst::Evaluated(expr)))
+        };
+
+        let last = &path.segments.last().unwrap().ident;
+        let name = last.to_string();
+        let known = STATUS_CONDITIONS.iter().find(|(variant, lua_const, _)| name == *variant || name == *lua_const);
+        if let Some((variant, _, _)) = known {
+            Ok(StatusCondition::Known(syn::Ident::new(variant, last.span())))
+        } else {
+            let variants: Vec<&str> = STATUS_CONDITIONS.iter().map(|(variant, _, _)| *variant).collect();
+            Err(syn::Error::new_spanned(path, format!(
+                "Unknown status condition '{}'. Expected one of {}, or '{}' for anything else.",
+                name.bright_red(), variants.join(", "), "raw(...)".bright_blue()
+            )))
+        }
+    }
+}
+
 pub struct StatusAttrs {
     pub agent: Hashable,
-    pub status: LuaConst,
-    pub condition: LuaConst,
-    pub low_priority: syn::LitBool
+    pub statuses: Vec<StatusKind>,
+    pub conditions: Vec<StatusCondition>,
+    pub low_priority: syn::LitBool,
+    pub enabled_if: Option<EnabledIf>,
+    pub manual: bool
+}
+
+impl StatusAttrs {
+    /// Every (status, condition) pair this script gets registered for.
+    pub fn registrations(&self) -> Vec<(&StatusKind, &StatusCondition)> {
+        self.statuses.iter().flat_map(|status| {
+            self.conditions.iter().map(move |condition| (status, condition))
+        }).collect()
+    }
 }
 
 impl Parse for StatusAttrs {
@@ -298,53 +629,74 @@ impl Parse for StatusAttrs {
 
         let _: Token![,] = input.parse()?;
 
-        let status = if input.peek(kw::status) {
-            let MetaItem::<kw::status, LuaConst> { item: lua_const, .. } = input.parse()?;
+        let statuses = if input.peek(kw::status) {
+            let MetaItem::<kw::status, StatusKind> { item: status_kind, .. } = input.parse()?;
 
-            Ok(lua_const)
+            Ok(vec![status_kind])
+        } else if input.peek(kw::statuses) {
+            let BracketedList::<kw::statuses, StatusKind, Token![,]> { list: status_kinds, .. } = input.parse()?;
+
+            Ok(status_kinds.into_iter().collect())
         } else {
             Err(input.error(format!(
-                "Expected keyword '{}' in macro declaration.", "status".bright_blue()
+                "Expected keyword '{}' or '{}' in macro declaration.", "status".bright_blue(), "statuses".bright_blue()
             )))
         }?;
 
         let _: Token![,] = input.parse()?;
 
-        let condition = if input.peek(kw::condition) {
-            let MetaItem::<kw::condition, LuaConst> { item: lua_const, .. } = input.parse()?;
+        let conditions = if input.peek(kw::condition) {
+            let MetaItem::<kw::condition, StatusCondition> { item: condition, .. } = input.parse()?;
 
-            Ok(lua_const)
+            Ok(vec![condition])
+        } else if input.peek(kw::conditions) {
+            let BracketedList::<kw::conditions, StatusCondition, Token![,]> { list: conditions, .. } = input.parse()?;
+
+            Ok(conditions.into_iter().collect())
         } else {
             Err(input.error(format!(
-                "Expected keyword '{}' in macro declaration.", "condition".bright_blue()
+                "Expected keyword '{}' or '{}' in macro declaration.", "condition".bright_blue(), "conditions".bright_blue()
             )))
         }?;
 
-        let low_priority = if let Ok(_) = input.parse::<Token![,]>() {
+        if statuses.is_empty() || conditions.is_empty() {
+            return Err(input.error("Status scripts must be registered for at least one status and condition."));
+        }
+
+        let mut low_priority = syn::LitBool::new(false, Span::call_site());
+        let mut enabled_if = None;
+        let mut manual = false;
+        while let Ok(_) = input.parse::<Token![,]>() {
             if let Ok(_) = input.parse::<kw::low_priority>() {
-                Ok(syn::LitBool::new(true, Span::call_site()))
+                low_priority = syn::LitBool::new(true, Span::call_site());
+            } else if input.peek(kw::enabled_if) {
+                enabled_if = Some(parse_enabled_if(input)?);
+            } else if let Ok(_) = input.parse::<kw::manual>() {
+                manual = true;
             } else {
-                Err(input.error(
+                return Err(input.error(
                     "Extra comma in macro declaration."
-                ))
+                ));
             }
-        } else {
-            Ok(syn::LitBool::new(false, Span::call_site()))
-        }?;
+        }
 
         Ok(Self {
             agent,
-            status,
-            condition,
-            low_priority
+            statuses,
+            conditions,
+            low_priority,
+            enabled_if,
+            manual
         })
     }
 }
 
 pub struct CommonStatusAttrs {
     pub status: LuaConst,
-    pub condition: LuaConst,
-    pub symbol: Option<syn::LitStr>
+    pub condition: StatusCondition,
+    pub symbol: Option<syn::LitStr>,
+    pub enabled_if: Option<EnabledIf>,
+    pub manual: bool
 }
 
 impl Parse for CommonStatusAttrs {
@@ -362,38 +714,48 @@ impl Parse for CommonStatusAttrs {
         let _: Token![,] = input.parse()?;
 
         let condition = if input.peek(kw::condition) {
-            let MetaItem::<kw::condition, LuaConst> { item: lua_const, .. } = input.parse()?;
+            let MetaItem::<kw::condition, StatusCondition> { item: condition, .. } = input.parse()?;
 
-            Ok(lua_const)
+            Ok(condition)
         } else {
             Err(input.error(format!(
                 "Expected keyword '{}' in macro declaration.", "condition".bright_blue()
             )))
         }?;
 
-        let symbol = if let Ok(_) = input.parse::<Token![,]>() {
-            if let Ok(MetaItem::<kw::symbol, syn::LitStr> { item: symbol, .. }) = input.parse::<MetaItem::<kw::symbol, syn::LitStr>>() {
-                Ok(Some(symbol))
+        let mut symbol = None;
+        let mut enabled_if = None;
+        let mut manual = false;
+        while let Ok(_) = input.parse::<Token![,]>() {
+            if input.peek(kw::symbol) {
+                let MetaItem::<kw::symbol, syn::LitStr> { item, .. } = input.parse()?;
+                symbol = Some(item);
+            } else if input.peek(kw::enabled_if) {
+                enabled_if = Some(parse_enabled_if(input)?);
+            } else if let Ok(_) = input.parse::<kw::manual>() {
+                manual = true;
             } else {
-                Err(input.error(
+                return Err(input.error(
                     "Extra comma in macro declaration."
-                ))
+                ));
             }
-        } else {
-            Ok(None)
-        }?;
+        }
 
         Ok(CommonStatusAttrs {
             status,
             condition,
-            symbol
+            symbol,
+            enabled_if,
+            manual
         })
     }
 }
 
 pub struct AgentFrameAttrs {
     pub agent: Option<LuaConst>,
-    pub is_replace: bool
+    pub is_replace: bool,
+    pub enabled_if: Option<EnabledIf>,
+    pub manual: bool
 }
 
 impl Parse for AgentFrameAttrs {
@@ -411,21 +773,28 @@ impl Parse for AgentFrameAttrs {
             )))
         }?;
 
-        let is_replace = if let Ok(_) = input.parse::<Token![,]>() {
+        let mut is_replace = false;
+        let mut enabled_if = None;
+        let mut manual = false;
+        while let Ok(_) = input.parse::<Token![,]>() {
             if let Ok(_) = input.parse::<Token![override]>() {
-                Ok(true)
+                is_replace = true;
+            } else if input.peek(kw::enabled_if) {
+                enabled_if = Some(parse_enabled_if(input)?);
+            } else if let Ok(_) = input.parse::<kw::manual>() {
+                manual = true;
             } else {
-                Err(input.error(
+                return Err(input.error(
                     "Extra comma in macro declaration."
-                ))
+                ));
             }
-        } else {
-            Ok(false)
-        }?;
+        }
 
         Ok(Self {
             agent,
-            is_replace
+            is_replace,
+            enabled_if,
+            manual
         })
     }
 }
\ No newline at end of file
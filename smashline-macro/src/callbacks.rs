@@ -5,25 +5,27 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{ToTokens, quote};
 
 use crate::attrs::*;
-use crate::{remove_mut, get_ident};
+use crate::{remove_mut, get_ident, gate_install_fn, gate_uninstall_fn, installer_entry};
 
 fn generate_fighter_install_fn(attrs: &AgentFrameAttrs, usr_fn_name: &syn::Ident, orig_name: &syn::Ident) -> TokenStream2 {
     let install_name = quote::format_ident!("{}_smashline_agent_frame_install", usr_fn_name);
     if let Some(agent) = &attrs.agent {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
-                unsafe { 
-                    smashline::replace_fighter_frame(#agent, Some(&mut #orig_name), #usr_fn_name);
+            pub fn #install_name() -> Result<smashline::AgentFrameRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
+                unsafe {
+                    smashline::replace_fighter_frame(#agent, Some(&mut #orig_name), #usr_fn_name)
                 }
             }
         ).into()
     } else {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::HookRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
                 unsafe {
-                    smashline::replace_symbol("common", "_ZN7lua2cpp16L2CFighterCommon31sys_line_system_control_fighterEv", #usr_fn_name as *const extern "C" fn(), Some(&mut #orig_name));
+                    smashline::replace_symbol("common", "_ZN7lua2cpp16L2CFighterCommon31sys_line_system_control_fighterEv", #usr_fn_name as *const extern "C" fn(), Some(&mut #orig_name), smashline::HookOrder::default())
                 }
             }
         ).into()
@@ -35,29 +37,61 @@ fn generate_weapon_install_fn(attrs: &AgentFrameAttrs, usr_fn_name: &syn::Ident,
     if let Some(agent) = &attrs.agent {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::AgentFrameRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
                 unsafe {
-                    smashline::replace_weapon_frame(#agent, Some(&mut #orig_name), #usr_fn_name);
+                    smashline::replace_weapon_frame(#agent, Some(&mut #orig_name), #usr_fn_name)
                 }
             }
         ).into()
     } else {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::HookRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
                 unsafe {
-                    smashline::replace_symbol("common", "_ZN7lua2cpp14L2CFighterBase23sys_line_system_controlEv", #usr_fn_name as *const extern "C" fn(), Some(&mut #orig_name));
+                    smashline::replace_symbol("common", "_ZN7lua2cpp14L2CFighterBase23sys_line_system_controlEv", #usr_fn_name as *const extern "C" fn(), Some(&mut #orig_name), smashline::HookOrder::default())
                 }
             }
         ).into()
     }
 }
 
+fn generate_uninstall_fn(attrs: &AgentFrameAttrs, usr_fn_name: &syn::Ident, orig_name: &syn::Ident, is_fighter: bool) -> TokenStream2 {
+    let uninstall_name = quote::format_ident!("{}_smashline_agent_frame_uninstall", usr_fn_name);
+    let restore = match (&attrs.agent, is_fighter) {
+        (Some(agent), true) => quote!(smashline::restore_fighter_frame(#agent, #usr_fn_name);),
+        (Some(agent), false) => quote!(smashline::restore_weapon_frame(#agent, #usr_fn_name);),
+        (None, true) => quote!(smashline::restore_symbol("common", "_ZN7lua2cpp16L2CFighterCommon31sys_line_system_control_fighterEv", #usr_fn_name as *const extern "C" fn());),
+        (None, false) => quote!(smashline::restore_symbol("common", "_ZN7lua2cpp14L2CFighterBase23sys_line_system_controlEv", #usr_fn_name as *const extern "C" fn());)
+    };
+    quote!(
+        #[allow(non_snake_case)]
+        pub fn #uninstall_name() {
+            if !smashline::is_runtime_available() {
+                return;
+            }
+            unsafe {
+                #restore
+                #orig_name = 0 as _;
+            }
+        }
+    )
+}
+
 pub fn install_agent_frame(input: TokenStream) -> TokenStream {
     let usr_fn_name = parse_macro_input!(input as syn::Ident);
     let install_name = quote::format_ident!("{}_smashline_agent_frame_install", usr_fn_name);
     quote!(
-        unsafe { #install_name() };
+        unsafe { #install_name() }
+    ).into()
+}
+
+pub fn uninstall_agent_frame(input: TokenStream) -> TokenStream {
+    let usr_fn_name = parse_macro_input!(input as syn::Ident);
+    let uninstall_name = quote::format_ident!("{}_smashline_agent_frame_uninstall", usr_fn_name);
+    quote!(
+        unsafe { #uninstall_name() };
     ).into()
 }
 
@@ -123,12 +157,19 @@ pub fn agent_frame(attrs: TokenStream, input: TokenStream, is_fighter: bool) ->
     } else {
         generate_weapon_install_fn(&attrs, &usr_fn_name, &orig_name)
     };
+    let install_fn = gate_install_fn(install_fn, &attrs.enabled_if, &usr_fn_name);
+    let uninstall_fn = gate_uninstall_fn(generate_uninstall_fn(&attrs, &usr_fn_name, &orig_name, is_fighter), &attrs.enabled_if, &usr_fn_name);
+    let installer = installer_entry(&usr_fn_name, "agent_frame", attrs.manual);
 
     quote!(
         #usr_fn
         
         #install_fn
 
+        #uninstall_fn
+
+        #installer
+
         #[allow(non_upper_case_globals)]
         static mut #orig_name: *const extern "C" fn() = 0 as _;
     ).into()
@@ -138,40 +179,86 @@ pub fn install_agent_reset(input: TokenStream) -> TokenStream {
     let usr_fn_name = parse_macro_input!(input as syn::Ident);
     let install_name = quote::format_ident!("{}_smashline_agent_reset_install", usr_fn_name);
     quote!(
-        #install_name();
+        #install_name()
     ).into()
 }
 
-pub fn agent_reset(input: TokenStream, is_fighter: bool) -> TokenStream {
+pub fn uninstall_agent_reset(input: TokenStream) -> TokenStream {
+    let usr_fn_name = parse_macro_input!(input as syn::Ident);
+    let uninstall_name = quote::format_ident!("{}_smashline_agent_reset_uninstall", usr_fn_name);
+    quote!(
+        #uninstall_name();
+    ).into()
+}
+
+pub fn agent_reset(attrs: TokenStream, input: TokenStream, is_fighter: bool) -> TokenStream {
+    let attrs = parse_macro_input!(attrs as CallbackAttrs);
     let usr_fn = parse_macro_input!(input as syn::ItemFn);
 
     let usr_fn_name = usr_fn.sig.ident.clone();
     let install_name = quote::format_ident!("{}_smashline_agent_reset_install", usr_fn_name);
+    let uninstall_name = quote::format_ident!("{}_smashline_agent_reset_uninstall", usr_fn_name);
 
     let install_fn = if is_fighter {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::CallbackRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
                 unsafe {
-                    smashline::add_fighter_reset_callback(#usr_fn_name);
+                    smashline::add_fighter_reset_callback(#usr_fn_name)
                 }
             }
         )
     } else {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::CallbackRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
                 unsafe {
-                    smashline::add_agent_reset_callback(#usr_fn_name);
+                    smashline::add_agent_reset_callback(#usr_fn_name)
                 }
             }
         )
     };
 
+    let uninstall_fn = if is_fighter {
+        quote!(
+            #[allow(non_snake_case)]
+            pub fn #uninstall_name() {
+                if !smashline::is_runtime_available() {
+                    return;
+                }
+                unsafe {
+                    smashline::remove_fighter_reset_callback(#usr_fn_name);
+                }
+            }
+        )
+    } else {
+        quote!(
+            #[allow(non_snake_case)]
+            pub fn #uninstall_name() {
+                if !smashline::is_runtime_available() {
+                    return;
+                }
+                unsafe {
+                    smashline::remove_agent_reset_callback(#usr_fn_name);
+                }
+            }
+        )
+    };
+
+    let install_fn = gate_install_fn(install_fn, &attrs.enabled_if, &usr_fn_name);
+    let uninstall_fn = gate_uninstall_fn(uninstall_fn, &attrs.enabled_if, &usr_fn_name);
+    let installer = installer_entry(&usr_fn_name, "agent_reset", attrs.manual);
+
     quote!(
         #usr_fn
 
         #install_fn
+
+        #uninstall_fn
+
+        #installer
     ).into()
 }
 
@@ -179,39 +266,85 @@ pub fn install_agent_frame_callback(input: TokenStream) -> TokenStream {
     let usr_fn_name = parse_macro_input!(input as syn::Ident);
     let install_name = quote::format_ident!("{}_smashline_agent_frame_callback_install", usr_fn_name);
     quote!(
-        #install_name();
+        #install_name()
+    ).into()
+}
+
+pub fn uninstall_agent_frame_callback(input: TokenStream) -> TokenStream {
+    let usr_fn_name = parse_macro_input!(input as syn::Ident);
+    let uninstall_name = quote::format_ident!("{}_smashline_agent_frame_callback_uninstall", usr_fn_name);
+    quote!(
+        #uninstall_name();
     ).into()
 }
 
-pub fn agent_frame_callback(input: TokenStream, is_fighter: bool) -> TokenStream {
+pub fn agent_frame_callback(attrs: TokenStream, input: TokenStream, is_fighter: bool) -> TokenStream {
+    let attrs = parse_macro_input!(attrs as CallbackAttrs);
     let usr_fn = parse_macro_input!(input as syn::ItemFn);
     let usr_fn_name = usr_fn.sig.ident.clone();
     let install_name = quote::format_ident!("{}_smashline_agent_frame_callback_install", usr_fn_name);
+    let uninstall_name = quote::format_ident!("{}_smashline_agent_frame_callback_uninstall", usr_fn_name);
 
     let install_fn = if is_fighter {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::CallbackRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
                 unsafe {
-                    smashline::add_fighter_frame_callback(#usr_fn_name);
+                    smashline::add_fighter_frame_callback(#usr_fn_name)
                 }
             }
         )
     } else {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::CallbackRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
                 unsafe {
-                    smashline::add_weapon_frame_callback(#usr_fn_name);
+                    smashline::add_weapon_frame_callback(#usr_fn_name)
                 }
             }
         )
     };
 
+    let uninstall_fn = if is_fighter {
+        quote!(
+            #[allow(non_snake_case)]
+            pub fn #uninstall_name() {
+                if !smashline::is_runtime_available() {
+                    return;
+                }
+                unsafe {
+                    smashline::remove_fighter_frame_callback(#usr_fn_name);
+                }
+            }
+        )
+    } else {
+        quote!(
+            #[allow(non_snake_case)]
+            pub fn #uninstall_name() {
+                if !smashline::is_runtime_available() {
+                    return;
+                }
+                unsafe {
+                    smashline::remove_weapon_frame_callback(#usr_fn_name);
+                }
+            }
+        )
+    };
+
+    let install_fn = gate_install_fn(install_fn, &attrs.enabled_if, &usr_fn_name);
+    let uninstall_fn = gate_uninstall_fn(uninstall_fn, &attrs.enabled_if, &usr_fn_name);
+    let installer = installer_entry(&usr_fn_name, "agent_frame_callback", attrs.manual);
+
     quote!(
         #usr_fn
 
         #install_fn
+
+        #uninstall_fn
+
+        #installer
     ).into()
 }
 
@@ -219,38 +352,84 @@ pub fn install_agent_init_callback(input: TokenStream) -> TokenStream {
     let usr_fn_name = parse_macro_input!(input as syn::Ident);
     let install_name = quote::format_ident!("{}_smashline_agent_init_callback_install", usr_fn_name);
     quote!(
-        #install_name();
+        #install_name()
     ).into()
 }
 
-pub fn agent_init_callback(input: TokenStream, is_fighter: bool) -> TokenStream {
+pub fn uninstall_agent_init_callback(input: TokenStream) -> TokenStream {
+    let usr_fn_name = parse_macro_input!(input as syn::Ident);
+    let uninstall_name = quote::format_ident!("{}_smashline_agent_init_callback_uninstall", usr_fn_name);
+    quote!(
+        #uninstall_name();
+    ).into()
+}
+
+pub fn agent_init_callback(attrs: TokenStream, input: TokenStream, is_fighter: bool) -> TokenStream {
+    let attrs = parse_macro_input!(attrs as CallbackAttrs);
     let usr_fn = parse_macro_input!(input as syn::ItemFn);
     let usr_fn_name = usr_fn.sig.ident.clone();
     let install_name = quote::format_ident!("{}_smashline_agent_init_callback_install", usr_fn_name);
+    let uninstall_name = quote::format_ident!("{}_smashline_agent_init_callback_uninstall", usr_fn_name);
 
     let install_fn = if is_fighter {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::CallbackRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
+                unsafe {
+                    smashline::add_fighter_init_callback(#usr_fn_name)
+                }
+            }
+        )
+    } else {
+        quote!(
+            #[allow(non_snake_case)]
+            pub fn #install_name() -> Result<smashline::CallbackRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
+                unsafe {
+                    smashline::add_agent_init_callback(#usr_fn_name)
+                }
+            }
+        )
+    };
+
+    let uninstall_fn = if is_fighter {
+        quote!(
+            #[allow(non_snake_case)]
+            pub fn #uninstall_name() {
+                if !smashline::is_runtime_available() {
+                    return;
+                }
                 unsafe {
-                    smashline::add_fighter_init_callback(#usr_fn_name);
+                    smashline::remove_fighter_init_callback(#usr_fn_name);
                 }
             }
         )
     } else {
         quote!(
             #[allow(non_snake_case)]
-            pub fn #install_name() {
+            pub fn #uninstall_name() {
+                if !smashline::is_runtime_available() {
+                    return;
+                }
                 unsafe {
-                    smashline::add_agent_init_callback(#usr_fn_name);
+                    smashline::remove_agent_init_callback(#usr_fn_name);
                 }
             }
         )
     };
 
+    let install_fn = gate_install_fn(install_fn, &attrs.enabled_if, &usr_fn_name);
+    let uninstall_fn = gate_uninstall_fn(uninstall_fn, &attrs.enabled_if, &usr_fn_name);
+    let installer = installer_entry(&usr_fn_name, "agent_init_callback", attrs.manual);
+
     quote!(
         #usr_fn
 
         #install_fn
+
+        #uninstall_fn
+
+        #installer
     ).into()
 }
\ No newline at end of file
@@ -5,66 +5,116 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{ToTokens, quote};
 
 use crate::attrs::*;
-use crate::remove_mut;
+use crate::{gate_install_fn, gate_uninstall_fn, installer_entry};
 
 use owo_colors::OwoColorize;
 
-fn generate_install_fn(module: &HookModule, symbol: &HookSymbol, usr_fn: &syn::Ident, orig_fn: &syn::Ident) -> impl ToTokens {
+// the address a `module = static` hook with a path, pattern or offset resolves to
+fn static_address(symbol: &HookSymbol, offset: &Option<syn::LitInt>, usr_fn: &syn::Ident) -> Option<TokenStream2> {
+    let base = match symbol {
+        HookSymbol::Resolved(symbol) => quote!(#symbol as *const () as usize),
+        HookSymbol::Pattern(pattern) => quote!(smashline::resolve_pattern(stringify!(#usr_fn), #pattern)?),
+        HookSymbol::Text => quote!(smashline::main_text().as_ptr() as usize),
+        HookSymbol::Unresolved(_) => return None
+    };
+
+    Some(match offset {
+        Some(offset) => quote!(#base + #offset),
+        None => base
+    })
+}
+
+fn generate_install_fn(attrs: &HookAttrs, usr_fn: &syn::Ident, orig_fn: &syn::Ident, address: &syn::Ident) -> impl ToTokens {
     let install_fn = quote::format_ident!(
         "{}_smashline_hook_install", usr_fn
     );
 
-    if let HookModule::Lazy(module) = module {
-        if let HookSymbol::Resolved(_symbol) = symbol {
-            syn::Error::new(module.span(), "Lazy (module) hooks cannot use resolved symbols.").into_compile_error()
-        } else if let HookSymbol::Unresolved(symbol) = symbol{
+    let order = attrs.order();
+    let orig_slot = orig_slot(orig_fn);
+    let replace_static = |symbol: TokenStream2| if attrs.inline {
+        quote!(smashline::replace_inline_hook(#symbol, #usr_fn as *const extern "C" fn()))
+    } else {
+        quote!(smashline::replace_static_symbol(#symbol, #usr_fn as *const extern "C" fn(), #orig_slot, #order))
+    };
+    let replace = match (&attrs.module, &attrs.symbol) {
+        (HookModule::Lazy(module), HookSymbol::Unresolved(symbol)) => quote! {
+            smashline::replace_symbol(#module, #symbol, #usr_fn as *const extern "C" fn(), #orig_slot, #order)
+        },
+        (HookModule::Lazy(module), _) => {
+            return syn::Error::new(module.span(), "Lazy (module) hooks cannot use resolved symbols.").into_compile_error()
+        },
+        (HookModule::Static(_), HookSymbol::Unresolved(symbol)) => replace_static(quote!(smashline::StaticSymbol::Unresolved(#symbol))),
+        (HookModule::Static(_), symbol) => {
+            let target = static_address(symbol, &attrs.offset, usr_fn).unwrap();
+            let replace = replace_static(quote!(smashline::StaticSymbol::Resolved(target)));
             quote! {
-                #[allow(non_snake_case)]
-                #[allow(unused_unsafe)]
-                pub fn #install_fn() {
-                    unsafe {
-                        if (smashline::replace_symbol as *const ()).is_null() {
-                            panic!("smashline::replace_symbol is missing -- maybe missing libsmashline_hook.nro?");
-                        }
-                        smashline::replace_symbol(#module, #symbol, #usr_fn as *const extern "C" fn(), Some(&mut #orig_fn));
-                    }
-                }
+                let target = #target;
+                let registration = #replace?;
+                #address = target;
+                Ok(registration)
             }
-        } else {
-            unreachable!()
         }
-    } else if let HookModule::Static(_) = module {
-        if let HookSymbol::Resolved(symbol) = symbol {
-            quote! {
-                #[allow(non_snake_case)]
-                #[allow(unused_unsafe)]
-                pub fn #install_fn() {
-                    unsafe {
-                        if (smashline::replace_static_symbol as *const ()).is_null() {
-                            panic!("smashline::replace_static_symbol is missing -- maybe missing libsmashline_hook.nro?");
-                        }
-                        smashline::replace_static_symbol(smashline::StaticSymbol::Resolved(#symbol as *const () as usize), #usr_fn as *const extern "C" fn(), Some(&mut #orig_fn));
-                    }
-                }
+    };
+
+    quote! {
+        #[allow(non_snake_case)]
+        #[allow(unused_unsafe)]
+        pub fn #install_fn() -> Result<smashline::HookRegistration, smashline::InstallError> {
+            smashline::check_runtime(stringify!(#usr_fn))?;
+            unsafe {
+                #replace
             }
-        } else if let HookSymbol::Unresolved(symbol) = symbol {
+        }
+    }
+}
+
+fn generate_uninstall_fn(attrs: &HookAttrs, usr_fn: &syn::Ident, orig_fn: &syn::Ident, address: &syn::Ident) -> impl ToTokens {
+    let uninstall_fn = quote::format_ident!(
+        "{}_smashline_hook_uninstall", usr_fn
+    );
+
+    let restore_static = |symbol: TokenStream2| if attrs.inline {
+        quote!(smashline::remove_inline_hook(#symbol, #usr_fn as *const extern "C" fn());)
+    } else {
+        quote!(smashline::restore_static_symbol(#symbol, #usr_fn as *const extern "C" fn());)
+    };
+    let restore = match (&attrs.module, &attrs.symbol) {
+        (HookModule::Lazy(module), HookSymbol::Unresolved(symbol)) => quote! {
+            smashline::restore_symbol(#module, #symbol, #usr_fn as *const extern "C" fn());
+        },
+        (HookModule::Static(_), HookSymbol::Unresolved(symbol)) => restore_static(quote!(smashline::StaticSymbol::Unresolved(#symbol))),
+        // patterns are only scanned for once, so the address found on install is reused here
+        (HookModule::Static(_), _) => {
+            let restore = restore_static(quote!(smashline::StaticSymbol::Resolved(#address)));
             quote! {
-                #[allow(non_snake_case)]
-                #[allow(unused_unsafe)]
-                pub fn #install_fn() {
-                    unsafe {
-                        if (smashline::replace_static_symbol as *const ()).is_null() {
-                            panic!("smashline::replace_static_symbol is missing -- maybe missing libsmashline_hook.nro?");
-                        }
-                        smashline::replace_static_symbol(smashline::StaticSymbol::Unresolved(#symbol), #usr_fn as *const extern "C" fn(), Some(&mut #orig_fn));
-                    }
+                if #address != 0 {
+                    #restore
+                    #address = 0;
                 }
             }
-        } else {
-            unreachable!()
-        }
+        },
+        // already reported by the install function
+        (HookModule::Lazy(_), _) => return TokenStream2::new()
+    };
+    // inline hooks have no original to call
+    let clear_orig = if attrs.inline {
+        TokenStream2::new()
     } else {
-        unreachable!()
+        quote!(#orig_fn = None;)
+    };
+
+    quote! {
+        #[allow(non_snake_case)]
+        #[allow(unused_unsafe)]
+        pub fn #uninstall_fn() {
+            if !smashline::is_runtime_available() {
+                return;
+            }
+            unsafe {
+                #restore
+                #clear_orig
+            }
+        }
     }
 }
 
@@ -74,14 +124,24 @@ pub fn install_hook(input: TokenStream) -> TokenStream {
         "{}_smashline_hook_install", name
     );
     quote!(
-        unsafe { #install_fn(); }
+        unsafe { #install_fn() }
+    ).into()
+}
+
+pub fn uninstall_hook(input: TokenStream) -> TokenStream {
+    let name = parse_macro_input!(input as syn::Ident);
+    let uninstall_fn = quote::format_ident!(
+        "{}_smashline_hook_uninstall", name
+    );
+    quote!(
+        unsafe { #uninstall_fn(); }
     ).into()
 }
 
 pub fn generate_hook_fn(attrs: &HookAttrs, mut replacement_fn: syn::ItemFn) -> TokenStream2 {
     let mut output = TokenStream2::new();
 
-    let HookAttrs { module, symbol } = attrs;
+    let HookAttrs { module, symbol, .. } = attrs;
 
     if let HookModule::Lazy(module) = &module {
         if let HookSymbol::Resolved(_symbol) = &symbol {
@@ -95,33 +155,37 @@ pub fn generate_hook_fn(attrs: &HookAttrs, mut replacement_fn: syn::ItemFn) -> T
         name: Some(syn::LitStr::new("C", Span::call_site()))
     });
 
-    let args_tokens = replacement_fn.sig.inputs.iter().map(remove_mut);
+    let mut args_types = Vec::new();
+    for arg in replacement_fn.sig.inputs.iter() {
+        match arg {
+            syn::FnArg::Typed(arg) => args_types.push(arg.ty.clone()),
+            syn::FnArg::Receiver(_) => return syn::Error::new_spanned(arg, "Hooks cannot take self.").into_compile_error()
+        }
+    }
+    let args_names: Vec<syn::Ident> = (0..args_types.len()).map(|idx| quote::format_ident!("arg{}", idx)).collect();
     let return_tokens = replacement_fn.sig.output.to_token_stream();
 
     let usr_fn = replacement_fn.sig.ident.clone();
+    let vis = replacement_fn.vis.clone();
 
     let orig_fn = quote::format_ident!(
         "{}_smashline_hook_orig", usr_fn
     );
 
+    let orig_type = quote!(extern "C" fn(#(#args_types),*) #return_tokens);
+
+    let original_fn = quote::format_ident!("{}_original_fn", usr_fn);
+    let original = quote::format_ident!("{}_original", usr_fn);
+
     // allow for original!() and call_original! like in skyline-rs
     let orig_macro: syn::Stmt = parse_quote! {
         macro_rules! original {
             () => {
                 {
                     #[allow(unused_unsafe)]
-                    if true {
-                        unsafe {
-                            if #orig_fn.is_null() {
-                                panic!("Error calling function hook {}, original function not in memory.", stringify!(#usr_fn));
-                            } else {
-                                std::mem::transmute::<_, extern "C" fn(#(#args_tokens),*) #return_tokens>(
-                                    #orig_fn as *const()
-                                )
-                            }
-                        }
-                    } else {
-                        unreachable!()
+                    match unsafe { #orig_fn } {
+                        Some(original) => original,
+                        None => panic!("Error calling function hook {}, original function not in memory.", stringify!(#usr_fn))
                     }
                 }
             }
@@ -142,12 +206,54 @@ pub fn generate_hook_fn(attrs: &HookAttrs, mut replacement_fn: syn::ItemFn) -> T
         #replacement_fn
 
         #[allow(non_upper_case_globals)]
-        pub static mut #orig_fn: *const extern "C" fn() = 0 as _;
+        #vis static mut #orig_fn: Option<#orig_type> = None;
+
+        // next to the hook rather than in a module of its own, so they also work for hooks declared in a function body
+        /// The next function in the hook's chain, `None` while the hook is not installed.
+        #[allow(non_snake_case)]
+        #[allow(dead_code)]
+        #vis fn #original_fn() -> Option<#orig_type> {
+            unsafe { #orig_fn }
+        }
+
+        /// Calls the next function in the hook's chain, so the original can be reached from outside the hook.
+        #[allow(non_snake_case)]
+        #[allow(dead_code)]
+        #vis fn #original(#(#args_names: #args_types),*) #return_tokens {
+            match #original_fn() {
+                Some(original) => original(#(#args_names),*),
+                None => panic!("Error calling function hook {}, original function not in memory.", stringify!(#usr_fn))
+            }
+        }
     ).to_tokens(&mut output);
 
     output.into()
 }
 
+// the runtime only knows untyped function pointers, `Option<extern "C" fn(..)>` has the same layout
+pub fn orig_slot(orig_fn: &syn::Ident) -> TokenStream2 {
+    quote!(Some(&mut *(&mut #orig_fn as *mut _ as *mut *const extern "C" fn())))
+}
+
+fn generate_inline_hook_fn(mut replacement_fn: syn::ItemFn) -> TokenStream2 {
+    if replacement_fn.sig.inputs.len() != 1 {
+        return syn::Error::new(replacement_fn.sig.paren_token.span, format!(
+            "Inline hooks take a single '{}' argument.", "&mut InlineCtx".bright_blue()
+        )).into_compile_error();
+    }
+    if let syn::ReturnType::Type(_, ty) = &replacement_fn.sig.output {
+        return syn::Error::new_spanned(ty, "Inline hooks cannot return a value, write to the registers in the context instead.").into_compile_error();
+    }
+
+    // extern "C"
+    replacement_fn.sig.abi = Some(syn::Abi {
+        extern_token: syn::token::Extern { span: Span::call_site() },
+        name: Some(syn::LitStr::new("C", Span::call_site()))
+    });
+
+    replacement_fn.into_token_stream()
+}
+
 pub fn hook(attrs: TokenStream, input: TokenStream) -> TokenStream {
     let replacement_fn = parse_macro_input!(input as syn::ItemFn);
     let attrs = parse_macro_input!(attrs as HookAttrs);
@@ -158,13 +264,30 @@ pub fn hook(attrs: TokenStream, input: TokenStream) -> TokenStream {
         "{}_smashline_hook_orig", usr_fn
     );
 
-    let without_install = generate_hook_fn(&attrs, replacement_fn);
+    let address = quote::format_ident!(
+        "{}_smashline_hook_address", usr_fn
+    );
+
+    let without_install = if attrs.inline {
+        generate_inline_hook_fn(replacement_fn)
+    } else {
+        generate_hook_fn(&attrs, replacement_fn)
+    };
 
-    let install_fn = generate_install_fn(&attrs.module, &attrs.symbol, &usr_fn, &orig_fn);
+    let install_fn = gate_install_fn(generate_install_fn(&attrs, &usr_fn, &orig_fn, &address), &attrs.enabled_if, &usr_fn);
+    let uninstall_fn = gate_uninstall_fn(generate_uninstall_fn(&attrs, &usr_fn, &orig_fn, &address), &attrs.enabled_if, &usr_fn);
+    let installer = installer_entry(&usr_fn, "hook", attrs.manual);
 
     quote!(
         #without_install
 
         #install_fn
+
+        #uninstall_fn
+
+        #installer
+
+        #[allow(non_upper_case_globals)]
+        static mut #address: usize = 0;
     ).into()
 }
\ No newline at end of file
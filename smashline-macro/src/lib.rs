@@ -1,12 +1,13 @@
 #![feature(asm)]
 #![feature(const_loop)]
 #![feature(const_if_match)]
-use syn::{Attribute, token, AttrStyle, Ident, parse_macro_input};
+use syn::{Attribute, token, AttrStyle, Ident, parse_macro_input, parse_quote};
 use proc_macro::TokenStream;
 use proc_macro2::{TokenStream as TokenStream2, Span};
 use quote::{quote, ToTokens};
 
 mod acmd_lang;
+mod acmd_signatures;
 mod acmd;
 mod attrs;
 mod callbacks;
@@ -53,6 +54,131 @@ pub(crate) fn new_attr(attr_name: &str, args: Option<&str>) -> syn::Attribute {
     }
 }
 
+// gives the tokens making up the item itself the span, the contents of its groups, such as the function body and
+// the user's tokens in it, keep their own, see `gate_install_fn`
+fn respan_item(tokens: TokenStream2, span: Span) -> TokenStream2 {
+    tokens.into_iter().map(|mut token| {
+        token.set_span(span);
+        token
+    }).collect()
+}
+
+// makes a generated `*_install` function honor the item's `enabled_if`
+//
+// the function itself is also spanned to the user's function, rustc doesn't report dead code coming from a macro
+// expansion, so this way an item that is never installed gets a `dead_code` warning pointing at it. With the
+// `install_all` feature, items that aren't `manual` are referenced by their `smashline_installers` entry and never warn
+pub(crate) fn gate_install_fn(install_fn: impl ToTokens, enabled_if: &Option<EnabledIf>, usr_fn_name: &syn::Ident) -> TokenStream2 {
+    let tokens = install_fn.into_token_stream();
+    // anything that isn't a function is a compile error which should be passed through as is
+    let mut install_fn: syn::ItemFn = match syn::parse2(tokens.clone()) {
+        Ok(install_fn) => install_fn,
+        Err(_) => return tokens
+    };
+
+    let installed = installed_flag(&install_fn.sig.ident);
+
+    if let Some(enabled_if) = enabled_if {
+        let predicate = &enabled_if.predicate;
+        let block = &install_fn.block;
+        install_fn.block = if enabled_if.per_match {
+            let output = match &install_fn.sig.output {
+                syn::ReturnType::Type(_, output) => output,
+                syn::ReturnType::Default => return tokens
+            };
+            parse_quote!({
+                let result: #output = (|| #block)();
+                if let Ok(registrations) = &result {
+                    smashline::enable_per_match(concat!(module_path!(), "::", stringify!(#usr_fn_name)), smashline::RegistrationIds::ids(registrations), #predicate);
+                }
+                result
+            })
+        } else {
+            parse_quote!({
+                if !#predicate() {
+                    return Err(smashline::InstallError::NotEnabled);
+                }
+                #block
+            })
+        };
+    }
+
+    let output = match &install_fn.sig.output {
+        syn::ReturnType::Type(_, output) => output.clone(),
+        syn::ReturnType::Default => return tokens
+    };
+    let block = &install_fn.block;
+    install_fn.block = parse_quote!({
+        let result: #output = (|| #block)();
+        if result.is_ok() {
+            #installed.set(true);
+        }
+        result
+    });
+
+    let install_fn = respan_item(install_fn.into_token_stream(), usr_fn_name.span());
+    quote!(
+        #install_fn
+
+        #[allow(non_upper_case_globals)]
+        static #installed: smashline::InstalledFlag = smashline::InstalledFlag::new();
+    )
+}
+
+// set by an item's install function and cleared by its uninstall function, so `smashline::install_all` can skip
+// items that were already installed by hand
+fn installed_flag(fn_name: &syn::Ident) -> syn::Ident {
+    let fn_name = fn_name.to_string();
+    let item = fn_name.trim_end_matches("_uninstall").trim_end_matches("_install");
+    quote::format_ident!("{}_installed", item)
+}
+
+pub(crate) fn gate_uninstall_fn(uninstall_fn: impl ToTokens, enabled_if: &Option<EnabledIf>, usr_fn_name: &syn::Ident) -> TokenStream2 {
+    let tokens = uninstall_fn.into_token_stream();
+    let mut uninstall_fn: syn::ItemFn = match syn::parse2(tokens.clone()) {
+        Ok(uninstall_fn) => uninstall_fn,
+        Err(_) => return tokens
+    };
+
+    let installed = installed_flag(&uninstall_fn.sig.ident);
+    uninstall_fn.block.stmts.insert(0, parse_quote! {
+        #installed.set(false);
+    });
+
+    if let Some(EnabledIf { per_match: true, .. }) = enabled_if {
+        uninstall_fn.block.stmts.insert(0, parse_quote! {
+            smashline::disable_per_match(concat!(module_path!(), "::", stringify!(#usr_fn_name)));
+        });
+    }
+
+    uninstall_fn.into_token_stream()
+}
+
+// adds the item to the `smashline_installers` section collected by `smashline::install_all`, which is opt-in since
+// the entry keeps the item's install function from ever being reported as unused
+pub(crate) fn installer_entry(usr_fn_name: &syn::Ident, kind: &str, manual: bool) -> TokenStream2 {
+    if manual || !cfg!(feature = "install_all") {
+        return TokenStream2::new();
+    }
+
+    let entry_name = quote::format_ident!("{}_smashline_{}_installer", usr_fn_name, kind);
+    let install_name = quote::format_ident!("{}_smashline_{}_install", usr_fn_name, kind);
+    let uninstall_name = quote::format_ident!("{}_smashline_{}_uninstall", usr_fn_name, kind);
+    let installed = installed_flag(&install_name);
+
+    quote!(
+        #[allow(non_upper_case_globals)]
+        #[link_section = "smashline_installers"]
+        #[used]
+        static #entry_name: smashline::Installer = smashline::Installer {
+            name: concat!(module_path!(), "::", stringify!(#usr_fn_name)),
+            install: || #install_name().map(|_| ()),
+            uninstall: #uninstall_name,
+            installed: &#installed
+        };
+    )
+}
+
 #[proc_macro_attribute]
 pub fn hook(attrs: TokenStream, input: TokenStream) -> TokenStream {
     hook::hook(attrs, input)
@@ -63,6 +189,11 @@ pub fn install_hook(input: TokenStream) -> TokenStream {
     hook::install_hook(input)
 }
 
+#[proc_macro]
+pub fn uninstall_hook(input: TokenStream) -> TokenStream {
+    hook::uninstall_hook(input)
+}
+
 #[proc_macro_derive(LuaStruct)]
 pub fn derive_lua_struct(item: TokenStream) -> TokenStream {
     derive::derive_lua_struct(item)
@@ -78,6 +209,11 @@ pub fn install_acmd_script(input: TokenStream) -> TokenStream {
     acmd::install_acmd_script(input)
 }
 
+#[proc_macro]
+pub fn uninstall_acmd_script(input: TokenStream) -> TokenStream {
+    acmd::uninstall_acmd_script(input)
+}
+
 #[proc_macro_attribute]
 pub fn status_script(attrs: TokenStream, input: TokenStream) -> TokenStream {
     status::status_script(attrs, input)
@@ -93,6 +229,11 @@ pub fn install_status_script(input: TokenStream) -> TokenStream {
     status::install_status_script(input)
 }
 
+#[proc_macro]
+pub fn uninstall_status_script(input: TokenStream) -> TokenStream {
+    status::uninstall_status_script(input)
+}
+
 #[proc_macro_attribute]
 pub fn fighter_frame(attrs: TokenStream, input: TokenStream) -> TokenStream {
     callbacks::agent_frame(attrs, input, true)
@@ -108,14 +249,19 @@ pub fn install_agent_frame(input: TokenStream) -> TokenStream {
     callbacks::install_agent_frame(input)
 }
 
+#[proc_macro]
+pub fn uninstall_agent_frame(input: TokenStream) -> TokenStream {
+    callbacks::uninstall_agent_frame(input)
+}
+
 #[proc_macro_attribute]
-pub fn fighter_reset(_: TokenStream, input: TokenStream) -> TokenStream {
-    callbacks::agent_reset(input, true)
+pub fn fighter_reset(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    callbacks::agent_reset(attrs, input, true)
 }
 
 #[proc_macro_attribute]
-pub fn agent_reset(_: TokenStream, input: TokenStream) -> TokenStream {
-    callbacks::agent_reset(input, false)
+pub fn agent_reset(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    callbacks::agent_reset(attrs, input, false)
 }
 
 #[proc_macro]
@@ -123,6 +269,11 @@ pub fn install_agent_reset(input: TokenStream) -> TokenStream {
     callbacks::install_agent_reset(input)
 }
 
+#[proc_macro]
+pub fn uninstall_agent_reset(input: TokenStream) -> TokenStream {
+    callbacks::uninstall_agent_reset(input)
+}
+
 #[proc_macro_attribute]
 pub fn installer(_: TokenStream, input: TokenStream) -> TokenStream {
     let mut usr_fn = parse_macro_input!(input as syn::ItemFn);
@@ -130,7 +281,18 @@ pub fn installer(_: TokenStream, input: TokenStream) -> TokenStream {
         extern_token: syn::token::Extern { span: Span::call_site() },
         name: Some(syn::LitStr::new("Rust", Span::call_site()))
     });
+    // refuse to register anything if libsmashline_hook.nro speaks a different ABI
+    usr_fn.block.stmts.insert(0, syn::parse_quote! {
+        if let Err(err) = smashline::check_abi_version() {
+            println!("[smashline] Refusing to install {}: {}", env!("CARGO_PKG_NAME"), err);
+            return;
+        }
+    });
     quote! (
+        #[export_name = "smashline_plugin_abi_version"]
+        #[used]
+        pub static SMASHLINE_PLUGIN_ABI_VERSION: u32 = smashline::ABI_VERSION;
+
         #[export_name = "smashline_install"]
         #usr_fn
     ).into()
@@ -150,13 +312,13 @@ pub fn uninstaller(_: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro_attribute]
-pub fn fighter_frame_callback(_: TokenStream, input: TokenStream) -> TokenStream {
-    callbacks::agent_frame_callback(input, true)
+pub fn fighter_frame_callback(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    callbacks::agent_frame_callback(attrs, input, true)
 }
 
 #[proc_macro_attribute]
-pub fn weapon_frame_callback(_: TokenStream, input: TokenStream) -> TokenStream {
-    callbacks::agent_frame_callback(input, false)
+pub fn weapon_frame_callback(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    callbacks::agent_frame_callback(attrs, input, false)
 }
 
 #[proc_macro]
@@ -164,6 +326,11 @@ pub fn install_agent_frame_callback(input: TokenStream) -> TokenStream {
     callbacks::install_agent_frame_callback(input)
 }
 
+#[proc_macro]
+pub fn uninstall_agent_frame_callback(input: TokenStream) -> TokenStream {
+    callbacks::uninstall_agent_frame_callback(input)
+}
+
 #[proc_macro]
 pub fn generate_acmd_is_execute(input: TokenStream) -> TokenStream {
     acmd_lang::generate_acmd_is_execute(input)
@@ -175,16 +342,26 @@ pub fn acmd(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro_attribute]
-pub fn fighter_init(_: TokenStream, input: TokenStream) -> TokenStream {
-    callbacks::agent_init_callback(input, true)
+pub fn acmd_helper(_: TokenStream, input: TokenStream) -> TokenStream {
+    acmd_lang::acmd_helper(input)
+}
+
+#[proc_macro_attribute]
+pub fn fighter_init(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    callbacks::agent_init_callback(attrs, input, true)
 }
 
 #[proc_macro_attribute]
-pub fn agent_init(_: TokenStream, input: TokenStream) -> TokenStream {
-    callbacks::agent_init_callback(input, false)
+pub fn agent_init(attrs: TokenStream, input: TokenStream) -> TokenStream {
+    callbacks::agent_init_callback(attrs, input, false)
 }
 
 #[proc_macro]
 pub fn install_agent_init_callback(input: TokenStream) -> TokenStream {
     callbacks::install_agent_init_callback(input)
+}
+
+#[proc_macro]
+pub fn uninstall_agent_init_callback(input: TokenStream) -> TokenStream {
+    callbacks::uninstall_agent_init_callback(input)
 }
\ No newline at end of file
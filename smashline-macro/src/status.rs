@@ -5,22 +5,151 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{ToTokens, quote};
 
 use crate::attrs::*;
-use crate::remove_mut;
+use crate::{remove_mut, new_attr, gate_install_fn, gate_uninstall_fn, installer_entry};
 
-fn generate_install_fn(attrs: &StatusAttrs, usr_fn_name: &syn::Ident, orig_name: &syn::Ident) -> impl ToTokens {
+fn is_l2c_value(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(path) = ty {
+        path.path.segments.last().map(|segment| segment.ident == "L2CValue").unwrap_or(false)
+    } else {
+        false
+    }
+}
+
+// the game calls every status function as `fn(agent, &L2CValue...) -> L2CValue`, a mismatch only shows up as a crash
+fn check_signature(conditions: &[&StatusCondition], sig: &syn::Signature) -> syn::Result<()> {
+    let mut checked: Vec<String> = Vec::new();
+    for condition in conditions {
+        let variant = match condition {
+            StatusCondition::Known(variant) => variant.to_string(),
+            StatusCondition::Raw(_) => continue
+        };
+        if checked.contains(&variant) {
+            continue;
+        }
+
+        let extra_args = STATUS_CONDITIONS.iter().find(|(name, _, _)| variant == *name).map(|(_, _, extra_args)| *extra_args).unwrap();
+        let expected = format!("fn(&mut L2CFighterCommon{}) -> L2CValue", ", &L2CValue".repeat(extra_args));
+
+        match &sig.output {
+            syn::ReturnType::Type(_, ty) if is_l2c_value(ty) => {},
+            syn::ReturnType::Type(_, ty) => return Err(syn::Error::new_spanned(ty, format!(
+                "{} status scripts must return L2CValue. Expected signature `{}`", variant, expected
+            ))),
+            syn::ReturnType::Default => return Err(syn::Error::new(sig.ident.span(), format!(
+                "{} status scripts must return L2CValue. Expected signature `{}`", variant, expected
+            )))
+        }
+
+        if sig.inputs.len() != extra_args + 1 {
+            return Err(syn::Error::new(sig.paren_token.span, format!(
+                "{} status scripts take {} argument(s), found {}. Expected signature `{}`", variant, extra_args + 1, sig.inputs.len(), expected
+            )));
+        }
+
+        for (idx, arg) in sig.inputs.iter().enumerate() {
+            let ty = match arg {
+                syn::FnArg::Typed(arg) => &*arg.ty,
+                syn::FnArg::Receiver(_) => return Err(syn::Error::new_spanned(arg, "Status scripts cannot take self."))
+            };
+            let valid = match ty {
+                syn::Type::Reference(reference) if idx == 0 => reference.mutability.is_some(),
+                syn::Type::Reference(reference) => is_l2c_value(&reference.elem),
+                _ => false
+            };
+            if !valid {
+                let expected_arg = if idx == 0 { "&mut L2CFighterCommon" } else { "&L2CValue" };
+                return Err(syn::Error::new_spanned(ty, format!(
+                    "Argument {} of {} status scripts must be `{}`. Expected signature `{}`", idx + 1, variant, expected_arg, expected
+                )));
+            }
+        }
+
+        checked.push(variant);
+    }
+
+    Ok(())
+}
+
+fn generate_install_fn(attrs: &StatusAttrs, usr_fn_name: &syn::Ident, orig_name: &syn::Ident, replacements: &[syn::Ident]) -> impl ToTokens {
     let install_name = quote::format_ident!("{}_smashline_status_script_install", usr_fn_name);
 
     let agent = &attrs.agent;
-    let status = &attrs.status;
-    let condition = &attrs.condition;
     let low_priority = &attrs.low_priority;
 
+    let registrations = attrs.registrations().into_iter().zip(replacements.iter()).enumerate().map(|(idx, ((status, condition), replacement))| {
+        let status = status.reserve(agent);
+        quote!(
+            registrations.push(smashline::replace_status_script(#agent, #status, #condition, Some(&mut #orig_name[#idx]), #low_priority, #replacement as *const extern "C" fn())?);
+        )
+    });
+    // the registrations hold the status kinds they were made for, so custom kinds don't have to be looked up again
+    let rollbacks = replacements.iter().enumerate().map(|(idx, replacement)| quote!(
+        if let Some(registration) = registrations.get(#idx) {
+            smashline::remove_status_script(
+                registration.agent,
+                smashline::LuaConstant::Evaluated(registration.status),
+                smashline::LuaConstant::Evaluated(registration.condition),
+                #replacement as *const extern "C" fn()
+            );
+            #orig_name[#idx] = 0 as _;
+        }
+    ));
+
     quote!(
         #[allow(non_snake_case)]
         #[allow(unused_unsafe)]
-        pub fn #install_name() {
+        pub fn #install_name() -> Result<Vec<smashline::StatusRegistration>, smashline::InstallError> {
+            smashline::check_runtime(stringify!(#usr_fn_name))?;
+            let mut registrations = Vec::new();
+            let result: Result<(), smashline::InstallError> = unsafe {
+                (|| {
+                    #(
+                        #registrations
+                    )*
+                    Ok(())
+                })()
+            };
+            if let Err(err) = result {
+                unsafe {
+                    #(
+                        #rollbacks
+                    )*
+                }
+                return Err(err);
+            }
+            Ok(registrations)
+        }
+    )
+}
+
+fn generate_uninstall_fn(attrs: &StatusAttrs, usr_fn_name: &syn::Ident, orig_name: &syn::Ident, replacements: &[syn::Ident]) -> impl ToTokens {
+    let uninstall_name = quote::format_ident!("{}_smashline_status_script_uninstall", usr_fn_name);
+
+    let agent = &attrs.agent;
+
+    let removals = attrs.registrations().into_iter().zip(replacements.iter()).enumerate().map(|(idx, ((status, condition), replacement))| {
+        let status = status.lookup(agent);
+        quote!(
+            match #status {
+                Some(status) => smashline::remove_status_script(#agent, status, #condition, #replacement as *const extern "C" fn()),
+                // reserving it now would only allocate a kind nothing was registered for
+                None => println!("[smashline] Could not uninstall {}: its custom status kind was never reserved", stringify!(#usr_fn_name))
+            }
+            #orig_name[#idx] = 0 as _;
+        )
+    });
+
+    quote!(
+        #[allow(non_snake_case)]
+        #[allow(unused_unsafe)]
+        pub fn #uninstall_name() {
+            if !smashline::is_runtime_available() {
+                return;
+            }
             unsafe {
-                smashline::replace_status_script(#agent, #status, #condition, Some(&mut #orig_name), #low_priority, #usr_fn_name as *const extern "C" fn());
+                #(
+                    #removals
+                )*
             }
         }
     )
@@ -31,7 +160,16 @@ pub fn install_status_script(input: TokenStream) -> TokenStream {
     let install_name = quote::format_ident!("{}_smashline_status_script_install", usr_fn_name);
 
     quote!(
-        unsafe { #install_name() };
+        unsafe { #install_name() }
+    ).into()
+}
+
+pub fn uninstall_status_script(input: TokenStream) -> TokenStream {
+    let usr_fn_name = parse_macro_input!(input as syn::Ident);
+    let uninstall_name = quote::format_ident!("{}_smashline_status_script_uninstall", usr_fn_name);
+
+    quote!(
+        unsafe { #uninstall_name() };
     ).into()
 }
 
@@ -39,18 +177,44 @@ pub fn status_script(attr: TokenStream, input: TokenStream) -> TokenStream {
     let attrs = parse_macro_input!(attr as StatusAttrs);
     let mut usr_fn = parse_macro_input!(input as syn::ItemFn);
 
+    if let Err(err) = check_signature(&attrs.conditions.iter().collect::<Vec<_>>(), &usr_fn.sig) {
+        return err.to_compile_error().into();
+    }
+
     let usr_fn_name = usr_fn.sig.ident.clone();
+    let usr_new_name = quote::format_ident!("{}_smashline_status_script_usr", usr_fn_name);
 
-    usr_fn.sig.abi = Some(syn::Abi {
+    let orig_name = quote::format_ident!("{}_smashline_status_script_orig", usr_fn_name);
+    let orig_arg = quote::format_ident!("_smashline_status_script_orig");
+
+    // the first registration keeps the user's name, so the script can still be called directly
+    let registration_count = attrs.registrations().len();
+    let replacements: Vec<syn::Ident> = (0..registration_count).map(|idx| {
+        if idx == 0 {
+            usr_fn_name.clone()
+        } else {
+            quote::format_ident!("{}_smashline_status_script_{}", usr_fn_name, idx)
+        }
+    }).collect();
+
+    let mut wrapper_sig = usr_fn.sig.clone();
+    wrapper_sig.abi = Some(syn::Abi {
         extern_token: token::Extern { span: Span::call_site() },
         name: Some(syn::LitStr::new("C", Span::call_site()))
     });
+    // the wrappers name the arguments themselves, the script's own patterns such as `_` can't be passed on
+    let args_names: Vec<syn::Ident> = (0..usr_fn.sig.inputs.len()).map(|idx| quote::format_ident!("__arg{}", idx)).collect();
+    wrapper_sig.inputs = usr_fn.sig.inputs.iter().zip(args_names.iter()).map(|(arg, name)| match arg {
+        syn::FnArg::Typed(arg) => {
+            let ty = &arg.ty;
+            parse_quote!(#name: #ty)
+        },
+        receiver => receiver.clone()
+    }).collect();
 
     let args_tokens = usr_fn.sig.inputs.iter().map(remove_mut);
     let return_tokens = usr_fn.sig.output.to_token_stream();
 
-    let orig_name = quote::format_ident!("{}_smashline_status_script_orig", usr_fn_name);
-
     let orig_macro: syn::Stmt = parse_quote! {
         macro_rules! original {
             ($($args:expr),* $(,)?) => {
@@ -58,10 +222,10 @@ pub fn status_script(attr: TokenStream, input: TokenStream) -> TokenStream {
                     #[allow(unused_unsafe)]
                     if true {
                         unsafe {
-                            if #orig_name.is_null() {
+                            if #orig_arg.is_null() {
                                 panic!("Error calling function hook {}, original function not in memory.", stringify!(#usr_fn_name));
                             } else {
-                                std::mem::transmute::<_, extern "C" fn(#(#args_tokens),*) #return_tokens>(#orig_name)($($args),*)
+                                std::mem::transmute::<_, extern "C" fn(#(#args_tokens),*) #return_tokens>(#orig_arg)($($args),*)
                             }
                         }
                     } else {
@@ -73,17 +237,43 @@ pub fn status_script(attr: TokenStream, input: TokenStream) -> TokenStream {
     };
 
     usr_fn.block.stmts.insert(0, orig_macro);
+    usr_fn.sig.ident = usr_new_name.clone();
+    usr_fn.sig.inputs.push(parse_quote! { #orig_arg: *const extern "C" fn() });
+    usr_fn.attrs.push(new_attr("inline", Some("(always)")));
+
+    let vis = &usr_fn.vis;
+    let wrappers = replacements.iter().enumerate().map(|(idx, replacement)| {
+        let mut sig = wrapper_sig.clone();
+        sig.ident = replacement.clone();
+        quote!(
+            #[allow(non_snake_case)]
+            #vis #sig {
+                #[allow(unused_unsafe)]
+                unsafe { #usr_new_name(#(#args_names,)* #orig_name[#idx]) }
+            }
+        )
+    });
 
-    let install_fn = generate_install_fn(&attrs, &usr_fn_name, &orig_name);
+    let install_fn = gate_install_fn(generate_install_fn(&attrs, &usr_fn_name, &orig_name, &replacements), &attrs.enabled_if, &usr_fn_name);
+    let uninstall_fn = gate_uninstall_fn(generate_uninstall_fn(&attrs, &usr_fn_name, &orig_name, &replacements), &attrs.enabled_if, &usr_fn_name);
+    let installer = installer_entry(&usr_fn_name, "status_script", attrs.manual);
 
     quote!(
         #usr_fn
 
+        #(
+            #wrappers
+        )*
+
         #install_fn
 
+        #uninstall_fn
+
+        #installer
+
         #[allow(non_snake_case)]
         #[allow(non_upper_case_globals)]
-        static mut #orig_name: *const extern "C" fn() = 0 as _;
+        static mut #orig_name: [*const extern "C" fn(); #registration_count] = [0 as _; #registration_count];
     ).into()
 }
 
@@ -91,37 +281,79 @@ pub fn common_status_script(attrs: TokenStream, input: TokenStream) -> TokenStre
     let attrs = parse_macro_input!(attrs as CommonStatusAttrs);
     let input = parse_macro_input!(input as syn::ItemFn);
 
+    if let Err(err) = check_signature(&[&attrs.condition], &input.sig) {
+        return err.to_compile_error().into();
+    }
 
     let hook_attrs = HookAttrs {
         module: HookModule::Lazy(syn::LitStr::new("common", Span::call_site())),
-        symbol: HookSymbol::Unresolved(attrs.symbol.unwrap_or(syn::LitStr::new("", Span::call_site())))
+        symbol: HookSymbol::Unresolved(attrs.symbol.unwrap_or(syn::LitStr::new("", Span::call_site()))),
+        offset: None,
+        priority: None,
+        before: Vec::new(),
+        after: Vec::new(),
+        inline: false,
+        // applied to the status script's install function instead
+        enabled_if: None,
+        manual: true
     };
 
     let usr_fn_name = input.sig.ident.clone();
     let install_name = quote::format_ident!("{}_smashline_status_script_install", usr_fn_name);
+    let uninstall_name = quote::format_ident!("{}_smashline_status_script_uninstall", usr_fn_name);
 
     let orig_name = quote::format_ident!("{}_smashline_hook_orig", usr_fn_name);
 
     let status = &attrs.status;
     let condition = &attrs.condition;
+    let order = hook_attrs.order();
+    let orig_slot = crate::hook::orig_slot(&orig_name);
 
     let hook_fn = crate::hook::generate_hook_fn(&hook_attrs, input);
     if let HookSymbol::Unresolved(symbol) = &hook_attrs.symbol {
         let install_fn = quote!(
-            pub fn #install_name() {
+            pub fn #install_name() -> Result<smashline::CommonStatusRegistration, smashline::InstallError> {
+                smashline::check_runtime(stringify!(#usr_fn_name))?;
                 unsafe {
                     if #symbol != "" {
-                        smashline::replace_symbol("common", #symbol, #usr_fn_name as *const extern "C" fn(), Some(&mut #orig_name));
-                        smashline::replace_common_status_script(#status, #condition, None, #usr_fn_name as *const extern "C" fn());
+                        let hook = smashline::replace_symbol("common", #symbol, #usr_fn_name as *const extern "C" fn(), #orig_slot, #order)?;
+                        // the runtime can refuse the script when it conflicts, which leaves the hook on its own
+                        let mut registration = smashline::replace_common_status_script(#status, #condition, None, #usr_fn_name as *const extern "C" fn()).map_err(|err| {
+                            smashline::restore_symbol("common", #symbol, #usr_fn_name as *const extern "C" fn());
+                            err
+                        })?;
+                        registration.hook = Some(hook);
+                        Ok(registration)
                     } else {
-                        smashline::replace_common_status_script(#status, #condition, Some(&mut #orig_name), #usr_fn_name as *const extern "C" fn());
+                        smashline::replace_common_status_script(#status, #condition, #orig_slot, #usr_fn_name as *const extern "C" fn())
                     }
                 }
             }
         );
 
+        let uninstall_fn = quote!(
+            pub fn #uninstall_name() {
+                if !smashline::is_runtime_available() {
+                    return;
+                }
+                unsafe {
+                    if #symbol != "" {
+                        smashline::restore_symbol("common", #symbol, #usr_fn_name as *const extern "C" fn());
+                    }
+                    smashline::remove_common_status_script(#status, #condition, #usr_fn_name as *const extern "C" fn());
+                    #orig_name = None;
+                }
+            }
+        );
+
+        let install_fn = gate_install_fn(install_fn, &attrs.enabled_if, &usr_fn_name);
+        let uninstall_fn = gate_uninstall_fn(uninstall_fn, &attrs.enabled_if, &usr_fn_name);
+        let installer = installer_entry(&usr_fn_name, "status_script", attrs.manual);
+
         quote!(
             #install_fn
+            #uninstall_fn
+            #installer
             #hook_fn
         ).into()
     } else {
@@ -0,0 +1,91 @@
+use crate::*;
+use crate::registry::{RegisteredItem, RegistryEntry};
+
+/// What happens when a plugin's registration replaces the same ACMD script, status script or agent frame as an
+/// existing one with the same priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Only report the conflict, the runtime decides which registration is called.
+    Report,
+    /// Don't make the new registration, the install function fails with `InstallError::Collision`.
+    KeepExisting,
+    /// Disable the existing registration so the new one is called. Only applies to registrations the plugin made
+    /// itself, conflicts with other plugins are reported instead.
+    ReplaceExisting
+}
+
+/// What a new registration replaces, as the runtime compares it against the existing ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictTarget {
+    Acmd { agent: Hash40, script: Hash40, category: AcmdCategory, priority: Priority },
+    Status { agent: Hash40, status: i32, condition: i32, priority: Priority },
+    CommonStatus { status: i32, condition: i32 },
+    FighterFrame { agent: i32 },
+    WeaponFrame { agent: i32 }
+}
+
+impl ConflictTarget {
+    /// Registrations only conflict when they replace the same thing with the same priority, hooks and callbacks
+    /// chain instead.
+    pub fn matches(&self, item: &RegisteredItem) -> bool {
+        match (self, item) {
+            (ConflictTarget::Acmd { agent, script, category, priority }, RegisteredItem::Acmd(existing)) => {
+                existing.agent == *agent && existing.script == *script && existing.category == *category && existing.priority == *priority
+            },
+            (ConflictTarget::Status { agent, status, condition, priority }, RegisteredItem::Status(existing)) => {
+                existing.agent == *agent && existing.status == *status && existing.condition == *condition && existing.priority == *priority
+            },
+            (ConflictTarget::CommonStatus { status, condition }, RegisteredItem::CommonStatus(existing)) => {
+                existing.status == *status && existing.condition == *condition
+            },
+            (ConflictTarget::FighterFrame { agent }, RegisteredItem::FighterFrame(existing)) => existing.agent == *agent,
+            (ConflictTarget::WeaponFrame { agent }, RegisteredItem::WeaponFrame(existing)) => existing.agent == *agent,
+            _ => false
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub existing: RegistryEntry,
+    /// The plugin making the new registration, named by its module like `existing.plugin`.
+    pub incoming_plugin: String,
+    pub incoming: ConflictTarget,
+    /// How the conflict was resolved, see `resolve_conflict`.
+    pub policy: ConflictPolicy
+}
+
+/// How the runtime resolves a conflict between `existing` and a new registration made by `incoming_plugin` under
+/// that plugin's `policy`. `ReplaceExisting` only replaces the plugin's own registrations and reports the others.
+pub fn resolve_conflict(policy: ConflictPolicy, existing: &RegistryEntry, incoming_plugin: &str) -> ConflictPolicy {
+    match policy {
+        ConflictPolicy::ReplaceExisting if existing.plugin != incoming_plugin => ConflictPolicy::Report,
+        policy => policy
+    }
+}
+
+/// The handler every plugin starts with, it logs the conflict with both plugins' names.
+pub fn log_conflict(conflict: &Conflict) {
+    let resolution = match conflict.policy {
+        ConflictPolicy::Report => "",
+        ConflictPolicy::KeepExisting => ", keeping the existing one",
+        ConflictPolicy::ReplaceExisting => ", replacing the existing one"
+    };
+    println!(
+        "[smashline] {} replaces {:?}, which {} already replaces{}",
+        conflict.incoming_plugin, conflict.incoming, conflict.existing.plugin, resolution
+    );
+}
+
+/// Sets how the runtime resolves conflicts with this plugin's new registrations and the handler it calls for
+/// every conflict the plugin is part of, whether its registration is the existing or the new one. The handler is
+/// called after the policy was applied. Plugins start out with `ConflictPolicy::Report` and `log_conflict`.
+pub fn set_conflict_resolution(policy: ConflictPolicy, handler: fn(&Conflict)) {
+    if !is_runtime_available() {
+        return;
+    }
+
+    unsafe {
+        register_conflict_resolution(policy, handler);
+    }
+}
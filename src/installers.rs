@@ -0,0 +1,64 @@
+use crate::{InstallError, InstalledFlag};
+
+/// An item's generated install and uninstall functions, collected from every smashline attribute not marked `manual`
+/// when the `install_all` feature is enabled.
+#[repr(C)]
+pub struct Installer {
+    pub name: &'static str,
+    pub install: fn() -> Result<(), InstallError>,
+    pub uninstall: fn(),
+    /// Set while the item is installed, whether by [`install_all`] or by hand.
+    pub installed: &'static InstalledFlag
+}
+
+impl Installer {
+    pub fn is_installed(&self) -> bool {
+        self.installed.get()
+    }
+}
+
+// defined by the linker around the `smashline_installers` section, null if no item was collected. Only their addresses
+// are used, so `Installer` not being FFI-safe doesn't matter
+#[allow(improper_ctypes)]
+extern "C" {
+    #[linkage = "extern_weak"]
+    static __start_smashline_installers: *const Installer;
+    #[linkage = "extern_weak"]
+    static __stop_smashline_installers: *const Installer;
+}
+
+/// Every item that [`install_all`] installs.
+pub fn installers() -> &'static [Installer] {
+    unsafe {
+        let start = __start_smashline_installers;
+        let stop = __stop_smashline_installers;
+        if start.is_null() || stop.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(start, (stop as usize - start as usize) / std::mem::size_of::<Installer>())
+        }
+    }
+}
+
+/// Installs every item that wasn't marked `manual` and isn't installed yet, returning the name and error of each one
+/// that failed. Items whose `enabled_if` predicate returned false are skipped rather than reported.
+///
+/// Fails with `InstallError::NoInstallers` without installing anything if no item was collected, which usually means
+/// the linker script doesn't keep the `smashline_installers` section.
+pub fn install_all() -> Result<Vec<(&'static str, InstallError)>, InstallError> {
+    if installers().is_empty() {
+        return Err(InstallError::NoInstallers);
+    }
+
+    Ok(installers().iter().filter(|installer| !installer.is_installed()).filter_map(|installer| match (installer.install)() {
+        Ok(()) | Err(InstallError::NotEnabled) => None,
+        Err(err) => Some((installer.name, err))
+    }).collect())
+}
+
+/// Uninstalls every item [`install_all`] knows about that is currently installed.
+pub fn uninstall_all() {
+    for installer in installers().iter().filter(|installer| installer.is_installed()) {
+        (installer.uninstall)();
+    }
+}
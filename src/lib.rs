@@ -1,4 +1,5 @@
 #![feature(proc_macro_hygiene)]
+#![feature(linkage)]
 
 use smash::phx::Hash40;
 use smash::lib::{L2CValue, LuaConst};
@@ -6,6 +7,30 @@ use smash::lua2cpp::*;
 
 pub use smashline_macro::*;
 
+mod registration;
+pub use registration::*;
+
+mod runtime;
+pub use runtime::*;
+
+pub mod pattern;
+
+pub mod registry;
+
+mod conflicts;
+pub use conflicts::*;
+
+#[cfg(feature = "install_all")]
+mod installers;
+#[cfg(feature = "install_all")]
+pub use installers::*;
+
+#[cfg(feature = "mock")]
+pub mod mock;
+
+#[cfg(feature = "mock")]
+pub use mock::abi::*;
+
 type FighterFrame = extern "C" fn(&mut L2CFighterCommon) -> L2CValue;
 type WeaponFrame = extern "C" fn(&mut L2CFighterBase) -> L2CValue;
 type FighterFrameCallback = fn(&mut L2CFighterCommon);
@@ -19,7 +44,7 @@ type AgentInit = fn(&mut L2CFighterBase);
 macro_rules! install_hooks {
     ($($fn:ident),* $(,)?) => {
         $(
-            smashline::install_hook!($fn);
+            smashline::log_install_error(stringify!($fn), smashline::install_hook!($fn));
         )*
     }
 }
@@ -28,7 +53,7 @@ macro_rules! install_hooks {
 macro_rules! install_acmd_scripts {
     ($($fn:ident),* $(,)?) => {
         $(
-            smashline::install_acmd_script!($fn);
+            smashline::log_install_error(stringify!($fn), smashline::install_acmd_script!($fn));
         )*
     }
 }
@@ -37,7 +62,7 @@ macro_rules! install_acmd_scripts {
 macro_rules! install_status_scripts {
     ($($fn:ident),* $(,)?) => {
         $(
-            smashline::install_status_script!($fn);
+            smashline::log_install_error(stringify!($fn), smashline::install_status_script!($fn));
         )*
     }
 }
@@ -46,7 +71,7 @@ macro_rules! install_status_scripts {
 macro_rules! install_agent_frames {
     ($($fn:ident),* $(,)?) => {
         $(
-            smashline::install_agent_frame!($fn);
+            smashline::log_install_error(stringify!($fn), smashline::install_agent_frame!($fn));
         )*
     }
 }
@@ -55,7 +80,7 @@ macro_rules! install_agent_frames {
 macro_rules! install_agent_resets {
     ($($fn:ident),* $(,)?) => {
         $(
-            smashline::install_agent_reset!($fn);
+            smashline::log_install_error(stringify!($fn), smashline::install_agent_reset!($fn));
         )*
     }
 }
@@ -64,7 +89,7 @@ macro_rules! install_agent_resets {
 macro_rules! install_agent_frame_callbacks {
     ($($fn:ident),* $(,)?) => {
         $(
-            smashline::install_agent_frame_callback!($fn); 
+            smashline::log_install_error(stringify!($fn), smashline::install_agent_frame_callback!($fn));
         )*
     }
 }
@@ -73,7 +98,70 @@ macro_rules! install_agent_frame_callbacks {
 macro_rules! install_agent_init_callbacks {
     ($($fn:ident),* $(,)?) => {
         $(
-            smashline::install_agent_init_callback!($fn);
+            smashline::log_install_error(stringify!($fn), smashline::install_agent_init_callback!($fn));
+        )*
+    }
+}
+
+#[macro_export]
+macro_rules! uninstall_hooks {
+    ($($fn:ident),* $(,)?) => {
+        $(
+            smashline::uninstall_hook!($fn);
+        )*
+    }
+}
+
+#[macro_export]
+macro_rules! uninstall_acmd_scripts {
+    ($($fn:ident),* $(,)?) => {
+        $(
+            smashline::uninstall_acmd_script!($fn);
+        )*
+    }
+}
+
+#[macro_export]
+macro_rules! uninstall_status_scripts {
+    ($($fn:ident),* $(,)?) => {
+        $(
+            smashline::uninstall_status_script!($fn);
+        )*
+    }
+}
+
+#[macro_export]
+macro_rules! uninstall_agent_frames {
+    ($($fn:ident),* $(,)?) => {
+        $(
+            smashline::uninstall_agent_frame!($fn);
+        )*
+    }
+}
+
+#[macro_export]
+macro_rules! uninstall_agent_resets {
+    ($($fn:ident),* $(,)?) => {
+        $(
+            smashline::uninstall_agent_reset!($fn);
+        )*
+    }
+}
+
+#[macro_export]
+macro_rules! uninstall_agent_frame_callbacks {
+    ($($fn:ident),* $(,)?) => {
+        $(
+            smashline::uninstall_agent_frame_callback!($fn);
+        )*
+    }
+}
+
+#[macro_export]
+macro_rules! uninstall_agent_init_callbacks {
+    ($($fn:ident),* $(,)?) => {
+        $(
+            smashline::uninstall_agent_init_callback!($fn);
         )*
     }
 }
@@ -88,7 +176,63 @@ pub enum LuaConstant {
     Evaluated(i32)
 }
 
+impl LuaConstant {
+    pub fn value(&self) -> i32 {
+        match self {
+            LuaConstant::Symbolic(constant) => **constant,
+            LuaConstant::Evaluated(value) => *value
+        }
+    }
+}
+
+/// The status functions a status script can replace, see `LUA_SCRIPT_STATUS_FUNC_*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusCondition {
+    Pre,
+    Main,
+    End,
+    Init,
+    Exec,
+    ExecStop,
+    Exit,
+    MapCorrection,
+    FixCamera,
+    FixPosSlow,
+    CheckDamage,
+    CheckAttack,
+    OnChangeLr,
+    LeaveStop,
+    NotifyEventGimmick,
+    CalcParam
+}
+
+impl From<StatusCondition> for LuaConstant {
+    fn from(condition: StatusCondition) -> Self {
+        use smash::lib::lua_const::*;
+
+        LuaConstant::Symbolic(match condition {
+            StatusCondition::Pre => LUA_SCRIPT_STATUS_FUNC_STATUS_PRE,
+            StatusCondition::Main => LUA_SCRIPT_STATUS_FUNC_STATUS_MAIN,
+            StatusCondition::End => LUA_SCRIPT_STATUS_FUNC_STATUS_END,
+            StatusCondition::Init => LUA_SCRIPT_STATUS_FUNC_INIT_STATUS,
+            StatusCondition::Exec => LUA_SCRIPT_STATUS_FUNC_EXEC_STATUS,
+            StatusCondition::ExecStop => LUA_SCRIPT_STATUS_FUNC_EXEC_STOP,
+            StatusCondition::Exit => LUA_SCRIPT_STATUS_FUNC_EXIT_STATUS,
+            StatusCondition::MapCorrection => LUA_SCRIPT_STATUS_FUNC_MAP_CORRECTION,
+            StatusCondition::FixCamera => LUA_SCRIPT_STATUS_FUNC_FIX_CAMERA,
+            StatusCondition::FixPosSlow => LUA_SCRIPT_STATUS_FUNC_FIX_POS_SLOW,
+            StatusCondition::CheckDamage => LUA_SCRIPT_STATUS_FUNC_CHECK_DAMAGE,
+            StatusCondition::CheckAttack => LUA_SCRIPT_STATUS_FUNC_CHECK_ATTACK,
+            StatusCondition::OnChangeLr => LUA_SCRIPT_STATUS_FUNC_ON_CHANGE_LR,
+            StatusCondition::LeaveStop => LUA_SCRIPT_STATUS_FUNC_LEAVE_STOP,
+            StatusCondition::NotifyEventGimmick => LUA_SCRIPT_STATUS_FUNC_NOTIFY_EVENT_GIMMICK,
+            StatusCondition::CalcParam => LUA_SCRIPT_STATUS_FUNC_CALC_PARAM
+        })
+    }
+}
+
 #[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AcmdCategory {
     ACMD_GAME,
     ACMD_EFFECT,
@@ -98,23 +242,88 @@ pub enum AcmdCategory {
 
 pub use AcmdCategory::*;
 
+/// The registers an inline hook can read and modify, saved before the hooked instruction and restored after the hook returns.
+#[repr(C)]
+pub struct InlineCtx {
+    /// X0 through X30, where X29 is the frame pointer and X30 the link register.
+    pub registers: [u64; 31],
+    /// V0 through V31.
+    pub simd: [u128; 32]
+}
+
+impl InlineCtx {
+    pub fn x(&self, idx: usize) -> u64 {
+        self.registers[idx]
+    }
+
+    pub fn set_x(&mut self, idx: usize, value: u64) {
+        self.registers[idx] = value;
+    }
+
+    /// The single precision float in the low bits of `V{idx}`.
+    pub fn s(&self, idx: usize) -> f32 {
+        f32::from_bits(self.simd[idx] as u32)
+    }
+
+    /// Sets `S{idx}`, zeroing the rest of `V{idx}` like a scalar write does.
+    pub fn set_s(&mut self, idx: usize, value: f32) {
+        self.simd[idx] = value.to_bits() as u128;
+    }
+}
+
+#[cfg(not(feature = "mock"))]
 extern "Rust" {
-    pub fn replace_symbol(module: &str, symbol: &str, replace: *const extern "C" fn(), original: Option<&'static mut *const extern "C" fn()>);
-    pub fn replace_static_symbol(symbol: StaticSymbol, replace: *const extern "C" fn(), original: Option<&'static mut *const extern "C" fn()>);
+    pub fn get_abi_version() -> u32;
+
+    pub fn replace_symbol(module: &str, symbol: &str, replace: *const extern "C" fn(), original: Option<&'static mut *const extern "C" fn()>, order: HookOrder) -> Result<HookRegistration, InstallError>;
+    pub fn replace_static_symbol(symbol: StaticSymbol, replace: *const extern "C" fn(), original: Option<&'static mut *const extern "C" fn()>, order: HookOrder) -> Result<HookRegistration, InstallError>;
+
+    pub fn replace_acmd_script(agent: Hash40, script: Hash40, original: Option<&'static mut *const extern "C" fn()>, category: AcmdCategory, low_priority: bool, bind_fn: *const extern "C" fn()) -> Result<AcmdRegistration, InstallError>;
+    pub fn replace_status_script(agent: Hash40, script: LuaConstant, condition: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, low_priority: bool, replacement: *const extern "C" fn()) -> Result<StatusRegistration, InstallError>;
+    pub fn replace_common_status_script(script: LuaConstant, condition: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: *const extern "C" fn()) -> Result<CommonStatusRegistration, InstallError>;
+
+    pub fn replace_fighter_frame(agent: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: FighterFrame) -> Result<AgentFrameRegistration, InstallError>;
+    pub fn replace_weapon_frame(agent: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: WeaponFrame) -> Result<AgentFrameRegistration, InstallError>;
+
+    pub fn add_fighter_reset_callback(callback: FighterReset) -> Result<CallbackRegistration, InstallError>;
+    pub fn add_agent_reset_callback(callback: AgentReset) -> Result<CallbackRegistration, InstallError>;
+
+    pub fn add_fighter_frame_callback(callback: FighterFrameCallback) -> Result<CallbackRegistration, InstallError>;
+    pub fn add_weapon_frame_callback(callback: WeaponFrameCallback) -> Result<CallbackRegistration, InstallError>;
+
+    pub fn add_fighter_init_callback(callback: FighterInit) -> Result<CallbackRegistration, InstallError>;
+    pub fn add_agent_init_callback(callback: AgentInit) -> Result<CallbackRegistration, InstallError>;
+
+    pub fn replace_inline_hook(symbol: StaticSymbol, callback: *const extern "C" fn()) -> Result<HookRegistration, InstallError>;
+    pub fn remove_inline_hook(symbol: StaticSymbol, callback: *const extern "C" fn());
+
+    pub fn restore_symbol(module: &str, symbol: &str, replace: *const extern "C" fn());
+    pub fn restore_static_symbol(symbol: StaticSymbol, replace: *const extern "C" fn());
+
+    pub fn remove_acmd_script(agent: Hash40, script: Hash40, category: AcmdCategory, bind_fn: *const extern "C" fn());
+    pub fn remove_status_script(agent: Hash40, script: LuaConstant, condition: LuaConstant, replacement: *const extern "C" fn());
+    pub fn remove_common_status_script(script: LuaConstant, condition: LuaConstant, replacement: *const extern "C" fn());
+
+    pub fn restore_fighter_frame(agent: LuaConstant, replacement: FighterFrame);
+    pub fn restore_weapon_frame(agent: LuaConstant, replacement: WeaponFrame);
+
+    pub fn remove_fighter_reset_callback(callback: FighterReset);
+    pub fn remove_agent_reset_callback(callback: AgentReset);
+
+    pub fn remove_fighter_frame_callback(callback: FighterFrameCallback);
+    pub fn remove_weapon_frame_callback(callback: WeaponFrameCallback);
 
-    pub fn replace_acmd_script(agent: Hash40, script: Hash40, original: Option<&'static mut *const extern "C" fn()>, category: AcmdCategory, low_priority: bool, bind_fn: *const extern "C" fn());
-    pub fn replace_status_script(agent: Hash40, script: LuaConstant, condition: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, low_priority: bool, replacement: *const extern "C" fn());
-    pub fn replace_common_status_script(script: LuaConstant, condition: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: *const extern "C" fn());
+    pub fn remove_fighter_init_callback(callback: FighterInit);
+    pub fn remove_agent_init_callback(callback: AgentInit);
 
-    pub fn replace_fighter_frame(agent: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: FighterFrame);
-    pub fn replace_weapon_frame(agent: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: WeaponFrame);
+    pub fn reserve_status_kind(agent: Hash40, name: &str) -> i32;
+    pub fn get_status_kind(agent: Hash40, name: &str) -> Option<i32>;
 
-    pub fn add_fighter_reset_callback(callback: FighterReset);
-    pub fn add_agent_reset_callback(callback: AgentReset);
+    pub fn set_registration_enabled(id: RegistrationId, enabled: bool);
+    pub fn get_registration_state(id: RegistrationId) -> Option<RegistrationState>;
 
-    pub fn add_fighter_frame_callback(callback: FighterFrameCallback);
-    pub fn add_weapon_frame_callback(callback: WeaponFrameCallback);
+    pub fn get_registrations() -> Vec<registry::RegistryEntry>;
 
-    pub fn add_fighter_init_callback(callback: FighterInit);
-    pub fn add_agent_init_callback(callback: AgentInit);
+    // the runtime tells which plugin this is from the module `handler` is in
+    pub fn register_conflict_resolution(policy: ConflictPolicy, handler: fn(&Conflict));
 }
\ No newline at end of file
@@ -0,0 +1,808 @@
+//! A recording, in-process stand-in for the `libsmashline_hook.nro` ABI.
+//!
+//! With the `mock` feature enabled every function from the `extern "Rust"` block in the crate root is
+//! provided by [`abi`] instead of the hook plugin, so the installers generated by `#[acmd_script]`,
+//! `#[status_script]`, `#[hook]`, etc. can be called from host-side unit tests. Every call is recorded
+//! per-thread and can be inspected with [`take_registrations`].
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+use smash::phx::Hash40;
+use smash::lua2cpp::*;
+
+use crate::*;
+
+/// The `original` slot that an installer handed to the runtime.
+pub struct OriginalSlot(Option<*mut *const extern "C" fn()>);
+
+impl OriginalSlot {
+    fn new(original: Option<&'static mut *const extern "C" fn()>) -> Self {
+        Self(original.map(|slot| slot as *mut _))
+    }
+
+    pub fn is_some(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Reads the installer's original slot, `None` if it did not provide one.
+    ///
+    /// # Safety
+    /// The installer's `static mut` slot must not be written to concurrently.
+    pub unsafe fn get(&self) -> Option<*const extern "C" fn()> {
+        self.0.map(|slot| *slot)
+    }
+
+    /// Writes `function` into the installer's original slot, as the runtime would after resolving it.
+    ///
+    /// # Safety
+    /// The installer's `static mut` slot must not be accessed concurrently, and `function` must have the signature
+    /// the installer's original has.
+    pub unsafe fn set(&self, function: *const extern "C" fn()) {
+        if let Some(slot) = self.0 {
+            *slot = function;
+        }
+    }
+}
+
+pub struct SymbolRecord {
+    pub id: RegistrationId,
+    pub module: String,
+    pub symbol: String,
+    pub replacement: *const extern "C" fn(),
+    pub original: OriginalSlot,
+    pub order: HookOrder
+}
+
+pub struct StaticSymbolRecord {
+    pub id: RegistrationId,
+    pub symbol: StaticSymbol,
+    pub replacement: *const extern "C" fn(),
+    pub original: OriginalSlot,
+    pub order: HookOrder
+}
+
+pub struct InlineHookRecord {
+    pub id: RegistrationId,
+    pub symbol: StaticSymbol,
+    pub callback: *const extern "C" fn()
+}
+
+impl InlineHookRecord {
+    /// Calls the registered callback as if the hooked instruction was reached with the registers in `ctx`.
+    ///
+    /// # Safety
+    /// The callback may do anything an inline hook does, `ctx` has to hold what it expects.
+    pub unsafe fn invoke(&self, ctx: &mut InlineCtx) {
+        let callback: extern "C" fn(&mut InlineCtx) = std::mem::transmute(self.callback);
+        callback(ctx)
+    }
+}
+
+pub struct AcmdScriptRecord {
+    pub id: RegistrationId,
+    pub agent: Hash40,
+    pub script: Hash40,
+    pub category: AcmdCategory,
+    pub low_priority: bool,
+    pub bind_fn: *const extern "C" fn(),
+    pub original: OriginalSlot
+}
+
+impl AcmdScriptRecord {
+    /// Calls the registered bind function the same way the game calls ACMD scripts.
+    ///
+    /// # Safety
+    /// The script may do anything an ACMD script does, `agent` has to hold what it expects.
+    pub unsafe fn invoke(&self, agent: &mut L2CAgentBase) {
+        let bind_fn: extern "C" fn(&mut L2CAgentBase, *mut smash::lib::utility::Variadic) = std::mem::transmute(self.bind_fn);
+        bind_fn(agent, &0u64 as *const u64 as _)
+    }
+}
+
+pub struct StatusScriptRecord {
+    pub id: RegistrationId,
+    pub agent: Hash40,
+    pub status: LuaConstant,
+    pub condition: LuaConstant,
+    pub low_priority: bool,
+    pub replacement: *const extern "C" fn(),
+    pub original: OriginalSlot
+}
+
+pub struct CommonStatusScriptRecord {
+    pub id: RegistrationId,
+    pub status: LuaConstant,
+    pub condition: LuaConstant,
+    pub replacement: *const extern "C" fn(),
+    pub original: OriginalSlot
+}
+
+pub struct FighterFrameRecord {
+    pub id: RegistrationId,
+    pub agent: LuaConstant,
+    pub replacement: FighterFrame,
+    pub original: OriginalSlot
+}
+
+pub struct WeaponFrameRecord {
+    pub id: RegistrationId,
+    pub agent: LuaConstant,
+    pub replacement: WeaponFrame,
+    pub original: OriginalSlot
+}
+
+pub struct CallbackRecord<F> {
+    pub id: RegistrationId,
+    pub callback: F
+}
+
+pub enum Registration {
+    Symbol(SymbolRecord),
+    StaticSymbol(StaticSymbolRecord),
+    InlineHook(InlineHookRecord),
+    AcmdScript(AcmdScriptRecord),
+    StatusScript(StatusScriptRecord),
+    CommonStatusScript(CommonStatusScriptRecord),
+    FighterFrame(FighterFrameRecord),
+    WeaponFrame(WeaponFrameRecord),
+    FighterReset(CallbackRecord<FighterReset>),
+    AgentReset(CallbackRecord<AgentReset>),
+    FighterFrameCallback(CallbackRecord<FighterFrameCallback>),
+    WeaponFrameCallback(CallbackRecord<WeaponFrameCallback>),
+    FighterInit(CallbackRecord<FighterInit>),
+    AgentInit(CallbackRecord<AgentInit>)
+}
+
+impl Registration {
+    pub fn id(&self) -> RegistrationId {
+        match self {
+            Registration::Symbol(record) => record.id,
+            Registration::StaticSymbol(record) => record.id,
+            Registration::InlineHook(record) => record.id,
+            Registration::AcmdScript(record) => record.id,
+            Registration::StatusScript(record) => record.id,
+            Registration::CommonStatusScript(record) => record.id,
+            Registration::FighterFrame(record) => record.id,
+            Registration::WeaponFrame(record) => record.id,
+            Registration::FighterReset(record) => record.id,
+            Registration::AgentReset(record) => record.id,
+            Registration::FighterFrameCallback(record) => record.id,
+            Registration::WeaponFrameCallback(record) => record.id,
+            Registration::FighterInit(record) => record.id,
+            Registration::AgentInit(record) => record.id
+        }
+    }
+}
+
+// what each plugin set with `set_conflict_resolution`
+type ConflictResolution = (ConflictPolicy, fn(&Conflict));
+
+thread_local! {
+    static REGISTRATIONS: RefCell<Vec<Registration>> = RefCell::new(Vec::new());
+    static STATES: RefCell<HashMap<RegistrationId, RegistrationState>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<u64> = Cell::new(0);
+    static STATUS_KINDS: RefCell<HashMap<(u64, String), i32>> = RefCell::new(HashMap::new());
+    static MAIN_TEXT: Cell<&'static [u8]> = Cell::new(&[]);
+    static PLUGIN: RefCell<String> = RefCell::new(String::from("mock"));
+    static PLUGINS: RefCell<HashMap<RegistrationId, String>> = RefCell::new(HashMap::new());
+    static RUNTIME_LOADED: Cell<bool> = Cell::new(true);
+    static RUNTIME_ABI_VERSION: Cell<Option<u32>> = Cell::new(Some(ABI_VERSION));
+    static INSTALLED: RefCell<HashSet<usize>> = RefCell::new(HashSet::new());
+    static RESOLUTIONS: RefCell<HashMap<String, ConflictResolution>> = RefCell::new(HashMap::new());
+}
+
+/// First status kind handed out by the mocked `reserve_status_kind`.
+pub const CUSTOM_STATUS_KIND_START: i32 = 0x1000;
+
+fn next_id() -> RegistrationId {
+    NEXT_ID.with(|next_id| {
+        let id = next_id.get();
+        next_id.set(id + 1);
+        RegistrationId(id)
+    })
+}
+
+fn record(registration: Registration) {
+    STATES.with(|states| states.borrow_mut().insert(registration.id(), RegistrationState::Active));
+    PLUGINS.with(|plugins| plugins.borrow_mut().insert(registration.id(), current_plugin()));
+    REGISTRATIONS.with(|registrations| registrations.borrow_mut().push(registration));
+}
+
+fn remove(predicate: impl Fn(&Registration) -> bool) {
+    REGISTRATIONS.with(|registrations| registrations.borrow_mut().retain(|registration| {
+        if predicate(registration) {
+            STATES.with(|states| states.borrow_mut().remove(&registration.id()));
+            PLUGINS.with(|plugins| plugins.borrow_mut().remove(&registration.id()));
+            false
+        } else {
+            true
+        }
+    }));
+}
+
+/// The value the mock uses for `constant`. Symbolic constants are looked up in game memory, which doesn't exist on
+/// the host, so they stand in as a value derived from the constant itself, stable across calls and distinct per constant.
+pub fn lua_constant_value(constant: &LuaConstant) -> i32 {
+    match constant {
+        LuaConstant::Symbolic(constant) => {
+            // a `LuaConst` only holds the hash of the constant's name
+            let bytes = unsafe {
+                std::slice::from_raw_parts(constant as *const LuaConst as *const u8, std::mem::size_of::<LuaConst>())
+            };
+            let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3));
+            (hash >> 33) as i32
+        },
+        LuaConstant::Evaluated(value) => *value
+    }
+}
+
+fn same_constant(a: &LuaConstant, b: &LuaConstant) -> bool {
+    lua_constant_value(a) == lua_constant_value(b)
+}
+
+fn same_static_symbol(a: &StaticSymbol, b: &StaticSymbol) -> bool {
+    match (a, b) {
+        (StaticSymbol::Resolved(a), StaticSymbol::Resolved(b)) => a == b,
+        (StaticSymbol::Unresolved(a), StaticSymbol::Unresolved(b)) => a == b,
+        _ => false
+    }
+}
+
+// points the original slot of every hook on a target at the next hook in its chain, like the runtime does
+unsafe fn rewire_chain(same_target: impl Fn(&Registration) -> bool, removed: Option<*const extern "C" fn()>) {
+    REGISTRATIONS.with(|registrations| {
+        let registrations = registrations.borrow();
+        let plugins = PLUGINS.with(|plugins| plugins.borrow().clone());
+        let hooks: Vec<(&str, &HookOrder, *const extern "C" fn(), &OriginalSlot)> = registrations.iter().filter(|registration| same_target(registration)).filter_map(|registration| match registration {
+            Registration::Symbol(record) => Some((plugins[&record.id].as_str(), &record.order, record.replacement, &record.original)),
+            Registration::StaticSymbol(record) => Some((plugins[&record.id].as_str(), &record.order, record.replacement, &record.original)),
+            _ => None
+        }).collect();
+
+        let orders: Vec<(&str, &HookOrder)> = hooks.iter().map(|(plugin, order, _, _)| (*plugin, *order)).collect();
+        let chain = order_hook_chain(&orders);
+        for pair in chain.windows(2) {
+            hooks[pair[0]].3.set(hooks[pair[1]].2);
+        }
+
+        // the last hook calls the game's function, which only the test can provide, so just unlink it from the chain
+        if let Some(&last) = chain.last() {
+            let original = hooks[last].3.get();
+            if original.is_some() && (hooks.iter().any(|(_, _, replacement, _)| Some(*replacement) == original) || original == removed) {
+                hooks[last].3.set(0 as _);
+            }
+        }
+    });
+}
+
+/// Sets the bytes that pattern and offset hooks resolve against on the current thread, e.g. a dumped `main` text section.
+pub fn set_main_text(text: &'static [u8]) {
+    MAIN_TEXT.with(|main_text| main_text.set(text));
+}
+
+pub fn main_text() -> &'static [u8] {
+    MAIN_TEXT.with(|main_text| main_text.get())
+}
+
+/// Makes the current thread behave as if `libsmashline_hook.nro` was missing, or loaded again with `true`.
+pub fn set_runtime_loaded(loaded: bool) {
+    RUNTIME_LOADED.with(|runtime_loaded| runtime_loaded.set(loaded));
+}
+
+pub fn is_runtime_available() -> bool {
+    RUNTIME_LOADED.with(|runtime_loaded| runtime_loaded.get())
+}
+
+/// Sets the ABI version the mocked runtime reports, `None` for a runtime that predates versioning.
+pub fn set_runtime_abi_version(version: Option<u32>) {
+    RUNTIME_ABI_VERSION.with(|runtime_abi_version| runtime_abi_version.set(version));
+}
+
+pub fn runtime_abi_version() -> Option<u32> {
+    if is_runtime_available() {
+        RUNTIME_ABI_VERSION.with(|runtime_abi_version| runtime_abi_version.get())
+    } else {
+        None
+    }
+}
+
+/// Sets which plugin the registrations made on the current thread are reported as coming from, `"mock"` by default.
+pub fn set_plugin(plugin: &str) {
+    PLUGIN.with(|current| *current.borrow_mut() = plugin.to_owned());
+}
+
+pub(crate) fn current_plugin() -> String {
+    PLUGIN.with(|current| current.borrow().clone())
+}
+
+fn resolution(plugin: &str) -> ConflictResolution {
+    RESOLUTIONS.with(|resolutions| resolutions.borrow().get(plugin).copied()).unwrap_or((ConflictPolicy::Report, log_conflict))
+}
+
+// what the runtime does before every replacement that can conflict: the new plugin's policy resolves each enabled
+// registration for the same target, then the handlers of both plugins are told
+fn check_conflicts(target: ConflictTarget) -> Result<(), InstallError> {
+    let incoming_plugin = current_plugin();
+    let (policy, incoming_handler) = resolution(&incoming_plugin);
+
+    let existing: Vec<registry::RegistryEntry> = REGISTRATIONS.with(|registrations| registrations.borrow().iter().filter_map(|registration| {
+        let item = registered_item(registration);
+        let state = STATES.with(|states| states.borrow().get(&registration.id()).copied()).unwrap_or(RegistrationState::Active);
+        if state == RegistrationState::Disabled || !target.matches(&item) {
+            return None;
+        }
+        Some(registry::RegistryEntry {
+            plugin: PLUGINS.with(|plugins| plugins.borrow()[&registration.id()].clone()),
+            state,
+            item
+        })
+    }).collect());
+
+    let mut collision = false;
+    for existing in existing {
+        let policy = resolve_conflict(policy, &existing, &incoming_plugin);
+        match policy {
+            ConflictPolicy::Report => {},
+            ConflictPolicy::KeepExisting => collision = true,
+            ConflictPolicy::ReplaceExisting => unsafe { abi::set_registration_enabled(existing.item.id(), false) }
+        }
+
+        let (_, existing_handler) = resolution(&existing.plugin);
+        let same_plugin = existing.plugin == incoming_plugin;
+        let conflict = Conflict {
+            existing,
+            incoming_plugin: incoming_plugin.clone(),
+            incoming: target.clone(),
+            policy
+        };
+        incoming_handler(&conflict);
+        if !same_plugin {
+            existing_handler(&conflict);
+        }
+    }
+
+    if collision {
+        Err(InstallError::Collision)
+    } else {
+        Ok(())
+    }
+}
+
+fn static_address(symbol: &StaticSymbol) -> usize {
+    // unresolved symbols have nothing to be looked up in on the host
+    match symbol {
+        StaticSymbol::Resolved(address) => *address,
+        StaticSymbol::Unresolved(_) => 0
+    }
+}
+
+// what the runtime reports for a registration in `get_registrations`
+fn registered_item(registration: &Registration) -> registry::RegisteredItem {
+    use registry::{CallbackKind, RegisteredItem};
+
+    match registration {
+        Registration::Symbol(record) => RegisteredItem::Hook(HookRegistration {
+            id: record.id,
+            target: HookTarget::Lazy { module: record.module.clone(), symbol: record.symbol.clone() }
+        }),
+        Registration::StaticSymbol(record) => RegisteredItem::Hook(HookRegistration { id: record.id, target: HookTarget::Static(static_address(&record.symbol)) }),
+        Registration::InlineHook(record) => RegisteredItem::InlineHook(HookRegistration { id: record.id, target: HookTarget::Static(static_address(&record.symbol)) }),
+        Registration::AcmdScript(record) => RegisteredItem::Acmd(AcmdRegistration {
+            id: record.id,
+            agent: record.agent,
+            script: record.script,
+            category: record.category,
+            priority: Priority::from_low_priority(record.low_priority)
+        }),
+        Registration::StatusScript(record) => RegisteredItem::Status(StatusRegistration {
+            id: record.id,
+            agent: record.agent,
+            status: lua_constant_value(&record.status),
+            condition: lua_constant_value(&record.condition),
+            priority: Priority::from_low_priority(record.low_priority)
+        }),
+        Registration::CommonStatusScript(record) => RegisteredItem::CommonStatus(CommonStatusRegistration {
+            id: record.id,
+            status: lua_constant_value(&record.status),
+            condition: lua_constant_value(&record.condition),
+            hook: None
+        }),
+        Registration::FighterFrame(record) => RegisteredItem::FighterFrame(AgentFrameRegistration { id: record.id, agent: lua_constant_value(&record.agent) }),
+        Registration::WeaponFrame(record) => RegisteredItem::WeaponFrame(AgentFrameRegistration { id: record.id, agent: lua_constant_value(&record.agent) }),
+        Registration::FighterReset(record) => RegisteredItem::Callback(CallbackKind::FighterReset, CallbackRegistration { id: record.id }),
+        Registration::AgentReset(record) => RegisteredItem::Callback(CallbackKind::AgentReset, CallbackRegistration { id: record.id }),
+        Registration::FighterFrameCallback(record) => RegisteredItem::Callback(CallbackKind::FighterFrame, CallbackRegistration { id: record.id }),
+        Registration::WeaponFrameCallback(record) => RegisteredItem::Callback(CallbackKind::WeaponFrame, CallbackRegistration { id: record.id }),
+        Registration::FighterInit(record) => RegisteredItem::Callback(CallbackKind::FighterInit, CallbackRegistration { id: record.id }),
+        Registration::AgentInit(record) => RegisteredItem::Callback(CallbackKind::AgentInit, CallbackRegistration { id: record.id })
+    }
+}
+
+/// Removes and returns every registration made on the current thread, in call order, forgetting their states and plugins.
+pub fn take_registrations() -> Vec<Registration> {
+    STATES.with(|states| states.borrow_mut().clear());
+    PLUGINS.with(|plugins| plugins.borrow_mut().clear());
+    REGISTRATIONS.with(|registrations| registrations.borrow_mut().drain(..).collect())
+}
+
+/// Calls `f` with every registration made on the current thread, in call order, leaving them in place.
+pub fn with_registrations<R>(f: impl FnOnce(&[Registration]) -> R) -> R {
+    REGISTRATIONS.with(|registrations| f(&registrations.borrow()))
+}
+
+/// Clears everything recorded on the current thread, including reserved status kinds, the main text, the plugin
+/// name, which items are installed, the plugins' conflict resolutions and whether and which version of the runtime
+/// is loaded, so the next test starts from a fresh runtime.
+pub fn reset() {
+    take_registrations();
+    NEXT_ID.with(|next_id| next_id.set(0));
+    STATUS_KINDS.with(|status_kinds| status_kinds.borrow_mut().clear());
+    MAIN_TEXT.with(|main_text| main_text.set(&[]));
+    PLUGIN.with(|plugin| *plugin.borrow_mut() = String::from("mock"));
+    RUNTIME_LOADED.with(|runtime_loaded| runtime_loaded.set(true));
+    RUNTIME_ABI_VERSION.with(|runtime_abi_version| runtime_abi_version.set(Some(ABI_VERSION)));
+    INSTALLED.with(|installed| installed.borrow_mut().clear());
+    RESOLUTIONS.with(|resolutions| resolutions.borrow_mut().clear());
+}
+
+/// Stands in for `smashline::InstalledFlag`, whose state is kept per thread like the rest of the mock's, so tests
+/// running in parallel don't see each other's items as installed.
+pub struct InstalledFlag(u8);
+
+impl InstalledFlag {
+    pub const fn new() -> Self {
+        InstalledFlag(0)
+    }
+
+    // every flag is a distinct static, so its address tells it apart
+    fn key(&self) -> usize {
+        self as *const InstalledFlag as usize
+    }
+
+    pub fn get(&self) -> bool {
+        INSTALLED.with(|installed| installed.borrow().contains(&self.key()))
+    }
+
+    pub fn set(&self, installed: bool) {
+        INSTALLED.with(|flags| if installed {
+            flags.borrow_mut().insert(self.key());
+        } else {
+            flags.borrow_mut().remove(&self.key());
+        });
+    }
+}
+
+/// Mirrors the `extern "Rust"` declarations in the crate root one-to-one.
+// the functions are unsafe to match the declarations, not because the mock needs anything from the caller
+#[allow(clippy::missing_safety_doc)]
+pub mod abi {
+    use super::*;
+
+    pub unsafe fn get_abi_version() -> u32 {
+        runtime_abi_version().expect("the mocked runtime doesn't export get_abi_version")
+    }
+
+    pub unsafe fn replace_symbol(module: &str, symbol: &str, replace: *const extern "C" fn(), original: Option<&'static mut *const extern "C" fn()>, order: HookOrder) -> Result<HookRegistration, InstallError> {
+        let id = next_id();
+        record(Registration::Symbol(SymbolRecord {
+            id,
+            module: module.to_owned(),
+            symbol: symbol.to_owned(),
+            replacement: replace,
+            original: OriginalSlot::new(original),
+            order
+        }));
+        rewire_chain(|registration| match registration {
+            Registration::Symbol(record) => record.module == module && record.symbol == symbol,
+            _ => false
+        }, None);
+        Ok(HookRegistration { id, target: HookTarget::Lazy { module: module.to_owned(), symbol: symbol.to_owned() } })
+    }
+
+    pub unsafe fn replace_static_symbol(symbol: StaticSymbol, replace: *const extern "C" fn(), original: Option<&'static mut *const extern "C" fn()>, order: HookOrder) -> Result<HookRegistration, InstallError> {
+        let id = next_id();
+        let address = static_address(&symbol);
+        let target = match &symbol {
+            StaticSymbol::Resolved(address) => StaticSymbol::Resolved(*address),
+            StaticSymbol::Unresolved(name) => StaticSymbol::Unresolved(name)
+        };
+        record(Registration::StaticSymbol(StaticSymbolRecord {
+            id,
+            symbol,
+            replacement: replace,
+            original: OriginalSlot::new(original),
+            order
+        }));
+        rewire_chain(|registration| match registration {
+            Registration::StaticSymbol(record) => same_static_symbol(&record.symbol, &target),
+            _ => false
+        }, None);
+        Ok(HookRegistration { id, target: HookTarget::Static(address) })
+    }
+
+    pub unsafe fn replace_inline_hook(symbol: StaticSymbol, callback: *const extern "C" fn()) -> Result<HookRegistration, InstallError> {
+        let id = next_id();
+        let address = static_address(&symbol);
+        record(Registration::InlineHook(InlineHookRecord {
+            id,
+            symbol,
+            callback
+        }));
+        Ok(HookRegistration { id, target: HookTarget::Static(address) })
+    }
+
+    pub unsafe fn replace_acmd_script(agent: Hash40, script: Hash40, original: Option<&'static mut *const extern "C" fn()>, category: AcmdCategory, low_priority: bool, bind_fn: *const extern "C" fn()) -> Result<AcmdRegistration, InstallError> {
+        check_conflicts(ConflictTarget::Acmd { agent, script, category, priority: Priority::from_low_priority(low_priority) })?;
+        let id = next_id();
+        record(Registration::AcmdScript(AcmdScriptRecord {
+            id,
+            agent,
+            script,
+            category,
+            low_priority,
+            bind_fn,
+            original: OriginalSlot::new(original)
+        }));
+        Ok(AcmdRegistration { id, agent, script, category, priority: Priority::from_low_priority(low_priority) })
+    }
+
+    pub unsafe fn replace_status_script(agent: Hash40, script: LuaConstant, condition: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, low_priority: bool, replacement: *const extern "C" fn()) -> Result<StatusRegistration, InstallError> {
+        let id = next_id();
+        let registration = StatusRegistration {
+            id,
+            agent,
+            status: lua_constant_value(&script),
+            condition: lua_constant_value(&condition),
+            priority: Priority::from_low_priority(low_priority)
+        };
+        check_conflicts(ConflictTarget::Status {
+            agent,
+            status: registration.status,
+            condition: registration.condition,
+            priority: registration.priority
+        })?;
+        record(Registration::StatusScript(StatusScriptRecord {
+            id,
+            agent,
+            status: script,
+            condition,
+            low_priority,
+            replacement,
+            original: OriginalSlot::new(original)
+        }));
+        Ok(registration)
+    }
+
+    pub unsafe fn replace_common_status_script(script: LuaConstant, condition: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: *const extern "C" fn()) -> Result<CommonStatusRegistration, InstallError> {
+        check_conflicts(ConflictTarget::CommonStatus { status: lua_constant_value(&script), condition: lua_constant_value(&condition) })?;
+        let id = next_id();
+        let registration = CommonStatusRegistration {
+            id,
+            status: lua_constant_value(&script),
+            condition: lua_constant_value(&condition),
+            hook: None
+        };
+        record(Registration::CommonStatusScript(CommonStatusScriptRecord {
+            id,
+            status: script,
+            condition,
+            replacement,
+            original: OriginalSlot::new(original)
+        }));
+        Ok(registration)
+    }
+
+    pub unsafe fn replace_fighter_frame(agent: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: FighterFrame) -> Result<AgentFrameRegistration, InstallError> {
+        check_conflicts(ConflictTarget::FighterFrame { agent: lua_constant_value(&agent) })?;
+        let id = next_id();
+        let registration = AgentFrameRegistration { id, agent: lua_constant_value(&agent) };
+        record(Registration::FighterFrame(FighterFrameRecord {
+            id,
+            agent,
+            replacement,
+            original: OriginalSlot::new(original)
+        }));
+        Ok(registration)
+    }
+
+    pub unsafe fn replace_weapon_frame(agent: LuaConstant, original: Option<&'static mut *const extern "C" fn()>, replacement: WeaponFrame) -> Result<AgentFrameRegistration, InstallError> {
+        check_conflicts(ConflictTarget::WeaponFrame { agent: lua_constant_value(&agent) })?;
+        let id = next_id();
+        let registration = AgentFrameRegistration { id, agent: lua_constant_value(&agent) };
+        record(Registration::WeaponFrame(WeaponFrameRecord {
+            id,
+            agent,
+            replacement,
+            original: OriginalSlot::new(original)
+        }));
+        Ok(registration)
+    }
+
+    pub unsafe fn add_fighter_reset_callback(callback: FighterReset) -> Result<CallbackRegistration, InstallError> {
+        let id = next_id();
+        record(Registration::FighterReset(CallbackRecord { id, callback }));
+        Ok(CallbackRegistration { id })
+    }
+
+    pub unsafe fn add_agent_reset_callback(callback: AgentReset) -> Result<CallbackRegistration, InstallError> {
+        let id = next_id();
+        record(Registration::AgentReset(CallbackRecord { id, callback }));
+        Ok(CallbackRegistration { id })
+    }
+
+    pub unsafe fn add_fighter_frame_callback(callback: FighterFrameCallback) -> Result<CallbackRegistration, InstallError> {
+        let id = next_id();
+        record(Registration::FighterFrameCallback(CallbackRecord { id, callback }));
+        Ok(CallbackRegistration { id })
+    }
+
+    pub unsafe fn add_weapon_frame_callback(callback: WeaponFrameCallback) -> Result<CallbackRegistration, InstallError> {
+        let id = next_id();
+        record(Registration::WeaponFrameCallback(CallbackRecord { id, callback }));
+        Ok(CallbackRegistration { id })
+    }
+
+    pub unsafe fn add_fighter_init_callback(callback: FighterInit) -> Result<CallbackRegistration, InstallError> {
+        let id = next_id();
+        record(Registration::FighterInit(CallbackRecord { id, callback }));
+        Ok(CallbackRegistration { id })
+    }
+
+    pub unsafe fn add_agent_init_callback(callback: AgentInit) -> Result<CallbackRegistration, InstallError> {
+        let id = next_id();
+        record(Registration::AgentInit(CallbackRecord { id, callback }));
+        Ok(CallbackRegistration { id })
+    }
+
+    pub unsafe fn restore_symbol(module: &str, symbol: &str, replace: *const extern "C" fn()) {
+        remove(|registration| match registration {
+            Registration::Symbol(record) => record.module == module && record.symbol == symbol && record.replacement == replace,
+            _ => false
+        });
+        rewire_chain(|registration| match registration {
+            Registration::Symbol(record) => record.module == module && record.symbol == symbol,
+            _ => false
+        }, Some(replace));
+    }
+
+    pub unsafe fn restore_static_symbol(symbol: StaticSymbol, replace: *const extern "C" fn()) {
+        remove(|registration| match registration {
+            Registration::StaticSymbol(record) => same_static_symbol(&record.symbol, &symbol) && record.replacement == replace,
+            _ => false
+        });
+        rewire_chain(|registration| match registration {
+            Registration::StaticSymbol(record) => same_static_symbol(&record.symbol, &symbol),
+            _ => false
+        }, Some(replace));
+    }
+
+    pub unsafe fn remove_inline_hook(symbol: StaticSymbol, callback: *const extern "C" fn()) {
+        remove(|registration| match registration {
+            Registration::InlineHook(record) => same_static_symbol(&record.symbol, &symbol) && record.callback == callback,
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_acmd_script(agent: Hash40, script: Hash40, category: AcmdCategory, bind_fn: *const extern "C" fn()) {
+        remove(|registration| match registration {
+            Registration::AcmdScript(record) => record.agent == agent && record.script == script && record.category == category && record.bind_fn == bind_fn,
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_status_script(agent: Hash40, script: LuaConstant, condition: LuaConstant, replacement: *const extern "C" fn()) {
+        remove(|registration| match registration {
+            Registration::StatusScript(record) => {
+                record.agent == agent && same_constant(&record.status, &script) && same_constant(&record.condition, &condition)
+                    && record.replacement == replacement
+            },
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_common_status_script(script: LuaConstant, condition: LuaConstant, replacement: *const extern "C" fn()) {
+        remove(|registration| match registration {
+            Registration::CommonStatusScript(record) => {
+                same_constant(&record.status, &script) && same_constant(&record.condition, &condition) && record.replacement == replacement
+            },
+            _ => false
+        });
+    }
+
+    pub unsafe fn restore_fighter_frame(agent: LuaConstant, replacement: FighterFrame) {
+        remove(|registration| match registration {
+            Registration::FighterFrame(record) => same_constant(&record.agent, &agent) && record.replacement as usize == replacement as usize,
+            _ => false
+        });
+    }
+
+    pub unsafe fn restore_weapon_frame(agent: LuaConstant, replacement: WeaponFrame) {
+        remove(|registration| match registration {
+            Registration::WeaponFrame(record) => same_constant(&record.agent, &agent) && record.replacement as usize == replacement as usize,
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_fighter_reset_callback(callback: FighterReset) {
+        remove(|registration| match registration {
+            Registration::FighterReset(record) => record.callback as usize == callback as usize,
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_agent_reset_callback(callback: AgentReset) {
+        remove(|registration| match registration {
+            Registration::AgentReset(record) => record.callback as usize == callback as usize,
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_fighter_frame_callback(callback: FighterFrameCallback) {
+        remove(|registration| match registration {
+            Registration::FighterFrameCallback(record) => record.callback as usize == callback as usize,
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_weapon_frame_callback(callback: WeaponFrameCallback) {
+        remove(|registration| match registration {
+            Registration::WeaponFrameCallback(record) => record.callback as usize == callback as usize,
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_fighter_init_callback(callback: FighterInit) {
+        remove(|registration| match registration {
+            Registration::FighterInit(record) => record.callback as usize == callback as usize,
+            _ => false
+        });
+    }
+
+    pub unsafe fn remove_agent_init_callback(callback: AgentInit) {
+        remove(|registration| match registration {
+            Registration::AgentInit(record) => record.callback as usize == callback as usize,
+            _ => false
+        });
+    }
+
+    pub unsafe fn reserve_status_kind(agent: Hash40, name: &str) -> i32 {
+        STATUS_KINDS.with(|status_kinds| {
+            let mut status_kinds = status_kinds.borrow_mut();
+            if let Some(kind) = status_kinds.get(&(agent.hash, name.to_owned())) {
+                return *kind;
+            }
+            let kind = CUSTOM_STATUS_KIND_START + status_kinds.keys().filter(|(hash, _)| *hash == agent.hash).count() as i32;
+            status_kinds.insert((agent.hash, name.to_owned()), kind);
+            kind
+        })
+    }
+
+    pub unsafe fn get_status_kind(agent: Hash40, name: &str) -> Option<i32> {
+        STATUS_KINDS.with(|status_kinds| status_kinds.borrow().get(&(agent.hash, name.to_owned())).copied())
+    }
+
+    pub unsafe fn set_registration_enabled(id: RegistrationId, enabled: bool) {
+        STATES.with(|states| {
+            if let Some(state) = states.borrow_mut().get_mut(&id) {
+                *state = if enabled { RegistrationState::Active } else { RegistrationState::Disabled };
+            }
+        });
+    }
+
+    pub unsafe fn get_registration_state(id: RegistrationId) -> Option<RegistrationState> {
+        STATES.with(|states| states.borrow().get(&id).copied())
+    }
+
+    pub unsafe fn get_registrations() -> Vec<registry::RegistryEntry> {
+        REGISTRATIONS.with(|registrations| registrations.borrow().iter().map(|registration| registry::RegistryEntry {
+            plugin: PLUGINS.with(|plugins| plugins.borrow()[&registration.id()].clone()),
+            state: get_registration_state(registration.id()).unwrap_or(RegistrationState::Active),
+            item: registered_item(registration)
+        }).collect())
+    }
+
+    pub unsafe fn register_conflict_resolution(policy: ConflictPolicy, handler: fn(&Conflict)) {
+        RESOLUTIONS.with(|resolutions| resolutions.borrow_mut().insert(current_plugin(), (policy, handler)));
+    }
+}
@@ -0,0 +1,171 @@
+//! Byte signature matching used to resolve `#[hook(symbol = pattern(...))]`.
+//!
+//! Nothing in here depends on the console, so the same patterns can be checked against a dumped `main`
+//! text section on the host, e.g. from CI before shipping against a new game version.
+
+/// A byte signature such as `"FD 7B ?? A9"`.
+///
+/// Every space-separated byte is two hex digits, where either digit can be `?` to match any nibble
+/// (`"F?"`, `"?9"`), and `??` (or a lone `?`) matches any byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    values: Vec<u8>,
+    masks: Vec<u8>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    Empty,
+    InvalidByte(String),
+    /// The bytes and mask passed to [`Pattern::with_mask`] differ in length.
+    MaskLength { bytes: usize, mask: usize }
+}
+
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PatternError::Empty => write!(f, "pattern is empty"),
+            PatternError::InvalidByte(byte) => write!(f, "invalid pattern byte `{}`, expected two hex digits or `??`", byte),
+            PatternError::MaskLength { bytes, mask } => write!(f, "pattern has {} bytes but its mask has {}", bytes, mask)
+        }
+    }
+}
+
+fn parse_nibble(nibble: char) -> Option<(u8, u8)> {
+    if nibble == '?' {
+        Some((0, 0))
+    } else {
+        nibble.to_digit(16).map(|value| (value as u8, 0xF))
+    }
+}
+
+fn parse_byte(byte: &str) -> Result<(u8, u8), PatternError> {
+    let invalid = || PatternError::InvalidByte(byte.to_owned());
+    let mut chars = byte.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some('?'), None, None) => Ok((0, 0)),
+        (Some(high), Some(low), None) => {
+            let (high_value, high_mask) = parse_nibble(high).ok_or_else(invalid)?;
+            let (low_value, low_mask) = parse_nibble(low).ok_or_else(invalid)?;
+            Ok((high_value << 4 | low_value, high_mask << 4 | low_mask))
+        },
+        _ => Err(invalid())
+    }
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Result<Self, PatternError> {
+        let (values, masks) = pattern.split_whitespace().map(parse_byte).collect::<Result<Vec<_>, _>>()?.into_iter().unzip();
+        Self::with_mask(values, masks)
+    }
+
+    /// Builds a pattern from raw bytes and a bitmask of which bits of each byte have to match.
+    pub fn with_mask(bytes: Vec<u8>, mask: Vec<u8>) -> Result<Self, PatternError> {
+        if bytes.len() != mask.len() {
+            Err(PatternError::MaskLength { bytes: bytes.len(), mask: mask.len() })
+        } else if bytes.is_empty() {
+            Err(PatternError::Empty)
+        } else {
+            let values = bytes.iter().zip(mask.iter()).map(|(byte, mask)| byte & mask).collect();
+            Ok(Self { values, masks: mask })
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Always false, `parse` and `with_mask` reject empty patterns.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns whether `bytes` starts with this pattern.
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        bytes.len() >= self.len() && self.values.iter().zip(self.masks.iter()).zip(bytes).all(|((value, mask), byte)| byte & mask == *value)
+    }
+
+    /// Returns the offset of every match in `haystack`, in ascending order. Matches may overlap.
+    pub fn find_all(&self, haystack: &[u8]) -> Vec<usize> {
+        if haystack.len() < self.len() {
+            return Vec::new();
+        }
+
+        let candidates = 0..=haystack.len() - self.len();
+
+        // only compare the whole pattern where its first fully known byte lines up, text dumps are tens of megabytes
+        match self.masks.iter().position(|mask| *mask == 0xFF) {
+            Some(anchor) => {
+                let value = self.values[anchor];
+                candidates.filter(|&offset| haystack[offset + anchor] == value && self.matches(&haystack[offset..])).collect()
+            },
+            None => candidates.filter(|&offset| self.matches(&haystack[offset..])).collect()
+        }
+    }
+
+    /// Returns the offset of the only match in `haystack`, or every match found if there isn't exactly one.
+    pub fn find_unique(&self, haystack: &[u8]) -> Result<usize, Vec<usize>> {
+        match self.find_all(haystack).as_slice() {
+            [offset] => Ok(*offset),
+            matches => Err(matches.to_vec())
+        }
+    }
+}
+
+impl std::str::FromStr for Pattern {
+    type Err = PatternError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        Self::parse(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &[u8] = &[0xFD, 0x7B, 0xBF, 0xA9, 0xFD, 0x03, 0x00, 0x91, 0xFD, 0x7B, 0xC1, 0xA9, 0xC0, 0x03, 0x5F, 0xD6];
+
+    #[test]
+    fn parses_bytes_and_wildcards() {
+        let pattern = Pattern::parse("FD 7b ?? ? F? ?3").unwrap();
+        assert_eq!(pattern.len(), 6);
+        assert_eq!(pattern, Pattern::with_mask(vec![0xFD, 0x7B, 0x00, 0x00, 0xF0, 0x03], vec![0xFF, 0xFF, 0x00, 0x00, 0xF0, 0x0F]).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert_eq!(Pattern::parse(""), Err(PatternError::Empty));
+        assert_eq!(Pattern::parse("  "), Err(PatternError::Empty));
+        assert_eq!(Pattern::parse("FD 7G"), Err(PatternError::InvalidByte("7G".to_owned())));
+        assert_eq!(Pattern::parse("FD7B"), Err(PatternError::InvalidByte("FD7B".to_owned())));
+        assert_eq!(Pattern::parse("F"), Err(PatternError::InvalidByte("F".to_owned())));
+        assert_eq!(Pattern::with_mask(vec![0xFD], vec![]), Err(PatternError::MaskLength { bytes: 1, mask: 0 }));
+    }
+
+    #[test]
+    fn finds_all_matches() {
+        assert_eq!(Pattern::parse("FD 7B ?? A9").unwrap().find_all(TEXT), vec![0, 8]);
+        assert_eq!(Pattern::parse("FD 7B B? A9").unwrap().find_all(TEXT), vec![0]);
+        assert_eq!(Pattern::parse("?D").unwrap().find_all(TEXT), vec![0, 4, 8]);
+        assert_eq!(Pattern::parse("?? ??").unwrap().find_all(TEXT).len(), TEXT.len() - 1);
+        assert_eq!(Pattern::parse("5F D6").unwrap().find_all(TEXT), vec![14]);
+        assert!(Pattern::parse("D6 00").unwrap().find_all(TEXT).is_empty());
+    }
+
+    #[test]
+    fn handles_short_haystacks() {
+        let pattern = Pattern::parse("FD 7B BF A9").unwrap();
+        assert!(pattern.find_all(&TEXT[..3]).is_empty());
+        assert!(pattern.find_all(&[]).is_empty());
+        assert!(!pattern.matches(&TEXT[..3]));
+        assert!(pattern.matches(TEXT));
+    }
+
+    #[test]
+    fn finds_unique_matches() {
+        assert_eq!(Pattern::parse("FD 03").unwrap().find_unique(TEXT), Ok(4));
+        assert_eq!(Pattern::parse("FD 7B").unwrap().find_unique(TEXT), Err(vec![0, 8]));
+        assert_eq!(Pattern::parse("AA BB").unwrap().find_unique(TEXT), Err(vec![]));
+    }
+}
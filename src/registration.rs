@@ -0,0 +1,238 @@
+use smash::phx::Hash40;
+
+use crate::AcmdCategory;
+
+/// Runtime-assigned identifier of a single registration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct RegistrationId(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    Normal,
+    Low
+}
+
+impl Priority {
+    pub fn from_low_priority(low_priority: bool) -> Self {
+        if low_priority {
+            Priority::Low
+        } else {
+            Priority::Normal
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationState {
+    /// The registration is the one that gets called.
+    Active,
+    /// Another plugin's registration for the same target takes precedence, e.g. because this one is `low_priority`.
+    Shadowed,
+    /// The registration was disabled through its handle.
+    Disabled
+}
+
+/// Where a hook lands among every plugin's hooks on the same function, see `#[hook(priority = ..., before = ..., after = ...)]`.
+///
+/// `before`/`after` name plugins the way `smashline::registry` does, by the module they were loaded from. The runtime
+/// tells which plugin a hook belongs to from the module its replacement lives in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HookOrder {
+    /// Hooks with a higher priority are called first.
+    pub priority: i32,
+    pub before: &'static [&'static str],
+    pub after: &'static [&'static str]
+}
+
+// whether the hook `(plugin, order)` has to be called before `(other_plugin, other)`
+fn precedes((plugin, order): (&str, &HookOrder), (other_plugin, other): (&str, &HookOrder)) -> bool {
+    order.before.contains(&other_plugin) || other.after.contains(&plugin)
+}
+
+/// Returns the order the hooks on one function are chained in, as indices into `hooks` starting with the one the game calls.
+/// Each hook is given with the plugin it belongs to.
+///
+/// `before`/`after` constraints take precedence over `priority`, and hooks that are otherwise equal stay in installation
+/// order. When the constraints form a cycle, the highest priority hook left in it is placed next.
+pub fn order_hook_chain(hooks: &[(&str, &HookOrder)]) -> Vec<usize> {
+    let mut remaining: Vec<usize> = (0..hooks.len()).collect();
+    let mut chain = Vec::with_capacity(hooks.len());
+
+    let rank = |idx: &usize| (hooks[*idx].1.priority, std::cmp::Reverse(*idx));
+
+    while !remaining.is_empty() {
+        let ready = remaining.iter().copied().filter(|&idx| {
+            !remaining.iter().any(|&other| other != idx && precedes(hooks[other], hooks[idx]))
+        }).max_by_key(rank);
+        let next = ready.unwrap_or_else(|| remaining.iter().copied().max_by_key(rank).unwrap());
+
+        remaining.retain(|&idx| idx != next);
+        chain.push(next);
+    }
+
+    chain
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallError {
+    /// `libsmashline_hook.nro` is not loaded, see `smashline::is_runtime_available`.
+    RuntimeMissing,
+    /// `libsmashline_hook.nro` speaks a different ABI version than this crate, see `smashline::ABI_VERSION`.
+    AbiMismatch { expected: u32, found: Option<u32> },
+    /// Another registration for the same target with the same priority takes precedence, e.g. because the plugin
+    /// uses `ConflictPolicy::KeepExisting`.
+    Collision,
+    /// The runtime could not resolve the agent, script, status or symbol.
+    InvalidTarget,
+    /// A `#[hook(symbol = pattern(...))]` signature does not occur in the main module.
+    PatternNotFound,
+    /// A `#[hook(symbol = pattern(...))]` signature occurs more than once in the main module.
+    AmbiguousPattern { matches: usize },
+    /// The item's `enabled_if` predicate returned false, so nothing was installed.
+    NotEnabled,
+    /// `smashline::install_all` found no items, the plugin's linker script has to keep the `smashline_installers`
+    /// section.
+    NoInstallers
+}
+
+impl std::fmt::Display for InstallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            InstallError::RuntimeMissing => write!(f, "libsmashline_hook.nro is missing or outdated"),
+            InstallError::AbiMismatch { expected, found: Some(found) } => write!(f, "smashline expects ABI version {} but libsmashline_hook.nro provides {}", expected, found),
+            InstallError::AbiMismatch { expected, found: None } => write!(f, "smashline expects ABI version {} but libsmashline_hook.nro predates ABI versioning", expected),
+            InstallError::Collision => write!(f, "target is already replaced by another plugin"),
+            InstallError::InvalidTarget => write!(f, "target could not be resolved"),
+            InstallError::PatternNotFound => write!(f, "pattern matches nowhere in the main module"),
+            InstallError::AmbiguousPattern { matches } => write!(f, "pattern matches {} locations in the main module, it must be unique", matches),
+            InstallError::NotEnabled => write!(f, "enabled_if predicate returned false"),
+            InstallError::NoInstallers => write!(f, "no items were collected, make sure the linker script keeps the smashline_installers section")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookTarget {
+    Lazy { module: String, symbol: String },
+    Static(usize)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookRegistration {
+    pub id: RegistrationId,
+    pub target: HookTarget
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcmdRegistration {
+    pub id: RegistrationId,
+    pub agent: Hash40,
+    pub script: Hash40,
+    pub category: AcmdCategory,
+    pub priority: Priority
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusRegistration {
+    pub id: RegistrationId,
+    pub agent: Hash40,
+    pub status: i32,
+    pub condition: i32,
+    pub priority: Priority
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommonStatusRegistration {
+    pub id: RegistrationId,
+    pub status: i32,
+    pub condition: i32,
+    /// Set when the script was also hooked by symbol, see `#[common_status_script(symbol = ...)]`.
+    pub hook: Option<HookRegistration>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentFrameRegistration {
+    pub id: RegistrationId,
+    pub agent: i32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackRegistration {
+    pub id: RegistrationId
+}
+
+/// Every registration an install function produced, see `enabled_if(..., per_match)`.
+pub trait RegistrationIds {
+    fn ids(&self) -> Vec<RegistrationId>;
+}
+
+impl<T: RegistrationIds> RegistrationIds for Vec<T> {
+    fn ids(&self) -> Vec<RegistrationId> {
+        self.iter().flat_map(|registration| registration.ids()).collect()
+    }
+}
+
+macro_rules! impl_registration {
+    ($($registration:ty),*) => {
+        $(
+            impl RegistrationIds for $registration {
+                fn ids(&self) -> Vec<RegistrationId> {
+                    vec![self.id]
+                }
+            }
+
+            impl $registration {
+                pub fn id(&self) -> RegistrationId {
+                    self.id
+                }
+
+                pub fn state(&self) -> Option<RegistrationState> {
+                    unsafe { crate::get_registration_state(self.id) }
+                }
+
+                pub fn enable(&self) {
+                    unsafe { crate::set_registration_enabled(self.id, true); }
+                }
+
+                pub fn disable(&self) {
+                    unsafe { crate::set_registration_enabled(self.id, false); }
+                }
+            }
+        )*
+    }
+}
+
+impl_registration!(HookRegistration, AcmdRegistration, StatusRegistration, AgentFrameRegistration, CallbackRegistration);
+
+impl RegistrationIds for CommonStatusRegistration {
+    fn ids(&self) -> Vec<RegistrationId> {
+        let mut ids = vec![self.id];
+        ids.extend(self.hook.as_ref().map(|hook| hook.id));
+        ids
+    }
+}
+
+impl CommonStatusRegistration {
+    pub fn id(&self) -> RegistrationId {
+        self.id
+    }
+
+    pub fn state(&self) -> Option<RegistrationState> {
+        unsafe { crate::get_registration_state(self.id) }
+    }
+
+    pub fn enable(&self) {
+        unsafe { crate::set_registration_enabled(self.id, true); }
+        if let Some(hook) = self.hook.as_ref() {
+            hook.enable();
+        }
+    }
+
+    pub fn disable(&self) {
+        unsafe { crate::set_registration_enabled(self.id, false); }
+        if let Some(hook) = self.hook.as_ref() {
+            hook.disable();
+        }
+    }
+}
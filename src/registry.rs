@@ -0,0 +1,102 @@
+//! Queries for what every loaded plugin currently has registered with `libsmashline_hook.nro`.
+//!
+//! The runtime is asked for a fresh snapshot on every call, so these are meant for debug overlays and log
+//! dumps rather than anything called per frame.
+
+use smash::phx::Hash40;
+
+use crate::*;
+
+/// The global callbacks smashline can register, see `#[fighter_reset]`, `#[fighter_frame_callback]`, etc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallbackKind {
+    FighterReset,
+    AgentReset,
+    FighterFrame,
+    WeaponFrame,
+    FighterInit,
+    AgentInit
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisteredItem {
+    Hook(HookRegistration),
+    InlineHook(HookRegistration),
+    Acmd(AcmdRegistration),
+    Status(StatusRegistration),
+    CommonStatus(CommonStatusRegistration),
+    FighterFrame(AgentFrameRegistration),
+    WeaponFrame(AgentFrameRegistration),
+    Callback(CallbackKind, CallbackRegistration)
+}
+
+impl RegisteredItem {
+    pub fn id(&self) -> RegistrationId {
+        match self {
+            RegisteredItem::Hook(registration) => registration.id,
+            RegisteredItem::InlineHook(registration) => registration.id,
+            RegisteredItem::Acmd(registration) => registration.id,
+            RegisteredItem::Status(registration) => registration.id,
+            RegisteredItem::CommonStatus(registration) => registration.id,
+            RegisteredItem::FighterFrame(registration) => registration.id,
+            RegisteredItem::WeaponFrame(registration) => registration.id,
+            RegisteredItem::Callback(_, registration) => registration.id
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryEntry {
+    /// The plugin the replacement belongs to, as named by the module it was loaded from.
+    pub plugin: String,
+    pub state: RegistrationState,
+    pub item: RegisteredItem
+}
+
+/// Every registration of every plugin, in the order they were made. Empty if `libsmashline_hook.nro` is not loaded.
+pub fn all() -> Vec<RegistryEntry> {
+    if !is_runtime_available() {
+        return Vec::new();
+    }
+
+    unsafe { get_registrations() }
+}
+
+pub fn acmd_for(agent: Hash40) -> Vec<RegistryEntry> {
+    all().into_iter().filter(|entry| match &entry.item {
+        RegisteredItem::Acmd(registration) => registration.agent == agent,
+        _ => false
+    }).collect()
+}
+
+pub fn status_for(agent: Hash40) -> Vec<RegistryEntry> {
+    all().into_iter().filter(|entry| match &entry.item {
+        RegisteredItem::Status(registration) => registration.agent == agent,
+        _ => false
+    }).collect()
+}
+
+pub fn common_status() -> Vec<RegistryEntry> {
+    all().into_iter().filter(|entry| matches!(entry.item, RegisteredItem::CommonStatus(_))).collect()
+}
+
+/// The fighter and weapon frames replaced for `agent`, a `FIGHTER_KIND_*` or `WEAPON_KIND_*` value.
+pub fn frames_for(agent: i32) -> Vec<RegistryEntry> {
+    all().into_iter().filter(|entry| match &entry.item {
+        RegisteredItem::FighterFrame(registration) | RegisteredItem::WeaponFrame(registration) => registration.agent == agent,
+        _ => false
+    }).collect()
+}
+
+pub fn callbacks() -> Vec<RegistryEntry> {
+    all().into_iter().filter(|entry| matches!(entry.item, RegisteredItem::Callback(..))).collect()
+}
+
+/// Function and inline hooks, including the ones smashline installs for global agent frames.
+pub fn hooks() -> Vec<RegistryEntry> {
+    all().into_iter().filter(|entry| matches!(entry.item, RegisteredItem::Hook(_) | RegisteredItem::InlineHook(_))).collect()
+}
+
+pub fn for_plugin(plugin: &str) -> Vec<RegistryEntry> {
+    all().into_iter().filter(|entry| entry.plugin == plugin).collect()
+}
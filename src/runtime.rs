@@ -0,0 +1,241 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::*;
+use crate::pattern::Pattern;
+
+/// Version of the `extern "Rust"` contract with `libsmashline_hook.nro`, bumped whenever a signature in it changes.
+pub const ABI_VERSION: u32 = 6;
+
+/// What the generated install functions do when `libsmashline_hook.nro` is not loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingRuntimePolicy {
+    Panic,
+    LogAndSkip,
+    ReturnError
+}
+
+static MISSING_RUNTIME_POLICY: AtomicU8 = AtomicU8::new(MissingRuntimePolicy::LogAndSkip as u8);
+
+pub fn set_missing_runtime_policy(policy: MissingRuntimePolicy) {
+    MISSING_RUNTIME_POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+pub fn missing_runtime_policy() -> MissingRuntimePolicy {
+    match MISSING_RUNTIME_POLICY.load(Ordering::Relaxed) {
+        0 => MissingRuntimePolicy::Panic,
+        1 => MissingRuntimePolicy::LogAndSkip,
+        _ => MissingRuntimePolicy::ReturnError
+    }
+}
+
+// the functions declared in the crate root's `extern "Rust"` block, looked up by name since the address of a declared
+// function is never null and can't tell whether `libsmashline_hook.nro` is loaded
+#[cfg(not(feature = "mock"))]
+const RUNTIME_SYMBOLS: &[&str] = &[
+    "replace_symbol\0",
+    "replace_static_symbol\0",
+    "replace_acmd_script\0",
+    "replace_status_script\0",
+    "replace_common_status_script\0",
+    "replace_fighter_frame\0",
+    "replace_weapon_frame\0",
+    "add_fighter_reset_callback\0",
+    "add_agent_reset_callback\0",
+    "add_fighter_frame_callback\0",
+    "add_weapon_frame_callback\0",
+    "add_fighter_init_callback\0",
+    "add_agent_init_callback\0",
+    "replace_inline_hook\0",
+    "remove_inline_hook\0",
+    "restore_symbol\0",
+    "restore_static_symbol\0",
+    "remove_acmd_script\0",
+    "remove_status_script\0",
+    "remove_common_status_script\0",
+    "restore_fighter_frame\0",
+    "restore_weapon_frame\0",
+    "remove_fighter_reset_callback\0",
+    "remove_agent_reset_callback\0",
+    "remove_fighter_frame_callback\0",
+    "remove_weapon_frame_callback\0",
+    "remove_fighter_init_callback\0",
+    "remove_agent_init_callback\0",
+    "reserve_status_kind\0",
+    "get_status_kind\0",
+    "set_registration_enabled\0",
+    "get_registration_state\0",
+    "get_registrations\0",
+    "register_conflict_resolution\0"
+];
+
+// `name` has to be nul terminated
+#[cfg(not(feature = "mock"))]
+fn is_symbol_loaded(name: &str) -> bool {
+    let mut address = 0usize;
+    unsafe {
+        skyline::nn::ro::LookupSymbol(&mut address, name.as_ptr());
+    }
+    address != 0
+}
+
+/// Returns whether every function smashline calls into is provided by `libsmashline_hook.nro`.
+#[cfg(not(feature = "mock"))]
+pub fn is_runtime_available() -> bool {
+    RUNTIME_SYMBOLS.iter().all(|symbol| is_symbol_loaded(symbol))
+}
+
+#[cfg(feature = "mock")]
+pub use crate::mock::is_runtime_available;
+
+/// The ABI version reported by `libsmashline_hook.nro`, or `None` if it is missing or predates versioning.
+#[cfg(not(feature = "mock"))]
+pub fn runtime_abi_version() -> Option<u32> {
+    // not part of `RUNTIME_SYMBOLS`, runtimes built before ABI versioning don't export it
+    if is_symbol_loaded("get_abi_version\0") {
+        Some(unsafe { get_abi_version() })
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "mock")]
+pub use crate::mock::runtime_abi_version;
+
+pub fn check_abi_version() -> Result<(), InstallError> {
+    match runtime_abi_version() {
+        Some(ABI_VERSION) => Ok(()),
+        runtime => Err(InstallError::AbiMismatch { expected: ABI_VERSION, found: runtime })
+    }
+}
+
+/// Called by every generated install function before touching the runtime, applying the current [`MissingRuntimePolicy`].
+pub fn check_runtime(item: &str) -> Result<(), InstallError> {
+    let result = if is_runtime_available() {
+        check_abi_version()
+    } else {
+        Err(InstallError::RuntimeMissing)
+    };
+
+    match (result, missing_runtime_policy()) {
+        (Ok(()), _) => Ok(()),
+        (Err(err), MissingRuntimePolicy::Panic) => panic!("smashline could not install {}: {}", item, err),
+        (Err(err), MissingRuntimePolicy::LogAndSkip) => {
+            println!("[smashline] Skipping {}: {}", item, err);
+            Err(err)
+        },
+        (Err(err), MissingRuntimePolicy::ReturnError) => Err(err)
+    }
+}
+
+/// Called by the `install_*!` batch macros, which have no result to return, to log why an item failed to install.
+/// Errors [`check_runtime`] already logged under [`MissingRuntimePolicy::LogAndSkip`] aren't repeated, and items
+/// skipped by their `enabled_if` predicate aren't errors.
+pub fn log_install_error<T>(item: &str, result: Result<T, InstallError>) {
+    match result {
+        Ok(_) | Err(InstallError::NotEnabled) => {},
+        Err(InstallError::RuntimeMissing) | Err(InstallError::AbiMismatch { .. })
+            if missing_runtime_policy() == MissingRuntimePolicy::LogAndSkip => {},
+        Err(err) => println!("[smashline] Failed to install {}: {}", item, err)
+    }
+}
+
+/// Whether an item is installed, set by its install function and cleared by its uninstall function.
+#[cfg(not(feature = "mock"))]
+pub struct InstalledFlag(AtomicBool);
+
+#[cfg(not(feature = "mock"))]
+impl InstalledFlag {
+    pub const fn new() -> Self {
+        InstalledFlag(AtomicBool::new(false))
+    }
+
+    pub fn get(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn set(&self, installed: bool) {
+        self.0.store(installed, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "mock")]
+pub use crate::mock::InstalledFlag;
+
+/// The `.text` section of the main module, which pattern and offset hooks are resolved against.
+#[cfg(not(feature = "mock"))]
+pub fn main_text() -> &'static [u8] {
+    use skyline::hooks::{getRegionAddress, Region};
+
+    unsafe {
+        let start = getRegionAddress(Region::Text) as *const u8;
+        let end = getRegionAddress(Region::Rodata) as *const u8;
+        std::slice::from_raw_parts(start, end as usize - start as usize)
+    }
+}
+
+#[cfg(feature = "mock")]
+pub use crate::mock::main_text;
+
+/// Resolves a `#[hook(symbol = pattern(...))]` signature to the address of its only match in [`main_text`].
+pub fn resolve_pattern(item: &str, pattern: &str) -> Result<usize, InstallError> {
+    let text = main_text();
+    let result = match Pattern::parse(pattern) {
+        Ok(parsed) => match parsed.find_unique(text) {
+            Ok(offset) => Ok(text.as_ptr() as usize + offset),
+            Err(matches) if matches.is_empty() => Err(InstallError::PatternNotFound),
+            Err(matches) => Err(InstallError::AmbiguousPattern { matches: matches.len() })
+        },
+        Err(_) => Err(InstallError::InvalidTarget)
+    };
+
+    if let Err(err) = &result {
+        println!("[smashline] Could not resolve pattern \"{}\" for {}: {}", pattern, item, err);
+    }
+
+    result
+}
+
+struct PerMatchItem {
+    item: &'static str,
+    ids: Vec<RegistrationId>,
+    predicate: fn() -> bool
+}
+
+static mut PER_MATCH_ITEMS: Vec<PerMatchItem> = Vec::new();
+static PER_MATCH_CALLBACK_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+fn reevaluate_per_match(_: &mut L2CFighterCommon) {
+    unsafe {
+        for item in PER_MATCH_ITEMS.iter() {
+            let enabled = (item.predicate)();
+            for id in item.ids.iter() {
+                set_registration_enabled(*id, enabled);
+            }
+        }
+    }
+}
+
+/// Called by the install function of an item with `enabled_if(predicate, per_match)`, which installs the item
+/// regardless and re-evaluates `predicate` whenever a fighter is initialized to enable or disable its registrations.
+pub fn enable_per_match(item: &'static str, ids: Vec<RegistrationId>, predicate: fn() -> bool) {
+    let enabled = predicate();
+    unsafe {
+        for id in ids.iter() {
+            set_registration_enabled(*id, enabled);
+        }
+
+        PER_MATCH_ITEMS.retain(|per_match| per_match.item != item);
+        PER_MATCH_ITEMS.push(PerMatchItem { item, ids, predicate });
+
+        if !PER_MATCH_CALLBACK_INSTALLED.swap(true, Ordering::Relaxed) && add_fighter_init_callback(reevaluate_per_match).is_err() {
+            PER_MATCH_CALLBACK_INSTALLED.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Called by the uninstall function of an item with `enabled_if(predicate, per_match)`.
+pub fn disable_per_match(item: &'static str) {
+    unsafe {
+        PER_MATCH_ITEMS.retain(|per_match| per_match.item != item);
+    }
+}
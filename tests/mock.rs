@@ -0,0 +1,430 @@
+#![cfg(feature = "mock")]
+#![feature(proc_macro_hygiene)]
+
+use smash::lib::L2CValue;
+use smash::lib::lua_const::*;
+use smash::lua2cpp::*;
+use smash::phx::Hash40;
+use smashline::*;
+use smashline::mock::{self, Registration};
+
+// installs an item, checks what the mock recorded and that uninstalling removes it again
+macro_rules! round_trip {
+    ($install:expr, $uninstall:ident!($($uninstall_args:tt)*), $pattern:pat) => {{
+        mock::reset();
+        let registration = $install.unwrap();
+        mock::with_registrations(|registrations| {
+            assert_eq!(registrations.len(), 1);
+            assert!(matches!(registrations[0], $pattern));
+        });
+        $uninstall!($($uninstall_args)*);
+        mock::with_registrations(|registrations| assert!(registrations.is_empty()));
+        registration
+    }};
+}
+
+#[hook(module = "common", symbol = "_ZN7lua2cpp16L2CFighterCommon17sub_mock_functionEv", manual)]
+unsafe fn symbol_hook(fighter: &mut L2CFighterCommon) -> L2CValue {
+    original!()(fighter)
+}
+
+#[hook(module = static, offset = 0x8, manual)]
+unsafe fn static_hook(x: u64) -> u64 {
+    original!()(x)
+}
+
+#[hook(module = static, offset = 0x4, inline, manual)]
+unsafe fn inline_hook(ctx: &mut InlineCtx) {
+    ctx.set_x(0, 1);
+}
+
+#[acmd_script(agent = "mario", script = "game_attack11", category = ACMD_GAME, manual)]
+fn mario_jab(_fighter: &mut L2CAgentBase) {}
+
+#[status_script(agent = "mario", status = FIGHTER_STATUS_KIND_ATTACK, condition = LUA_SCRIPT_STATUS_FUNC_STATUS_MAIN, manual)]
+unsafe fn mario_attack_main(fighter: &mut L2CFighterCommon) -> L2CValue {
+    original!(fighter)
+}
+
+#[status_script(agent = "mario", status = FIGHTER_STATUS_KIND_ATTACK, condition = CheckAttack, manual)]
+unsafe fn mario_attack_check_attack(_fighter: &mut L2CFighterCommon, _: &L2CValue, _: &L2CValue) -> L2CValue {
+    L2CValue::new_bool(false)
+}
+
+#[common_status_script(status = FIGHTER_STATUS_KIND_ATTACK, condition = LUA_SCRIPT_STATUS_FUNC_STATUS_PRE, manual)]
+unsafe fn common_attack_pre(fighter: &mut L2CFighterCommon) -> L2CValue {
+    original!()(fighter)
+}
+
+#[fighter_frame(agent = FIGHTER_KIND_MARIO, manual)]
+fn mario_frame(_fighter: &mut L2CFighterCommon) {}
+
+#[weapon_frame(agent = WEAPON_KIND_MARIO_FIREBALL, manual)]
+fn fireball_frame(_weapon: &mut L2CFighterBase) {}
+
+#[fighter_reset(manual)]
+fn fighter_reset(_fighter: &mut L2CFighterCommon) {}
+
+#[agent_reset(manual)]
+fn agent_reset(_agent: &mut L2CFighterBase) {}
+
+#[fighter_frame_callback(manual)]
+fn fighter_frame_callback(_fighter: &mut L2CFighterCommon) {}
+
+#[weapon_frame_callback(manual)]
+fn weapon_frame_callback(_weapon: &mut L2CFighterBase) {}
+
+#[fighter_init(manual)]
+fn fighter_init(_fighter: &mut L2CFighterCommon) {}
+
+#[agent_init(manual)]
+fn agent_init(_agent: &mut L2CFighterBase) {}
+
+static TEXT: [u8; 16] = [0; 16];
+
+#[test]
+fn hooks() {
+    round_trip!(install_hook!(symbol_hook), uninstall_hook!(symbol_hook), Registration::Symbol(_));
+
+    let registration = round_trip!({
+        mock::set_main_text(&TEXT);
+        install_hook!(static_hook)
+    }, uninstall_hook!(static_hook), Registration::StaticSymbol(_));
+    assert_eq!(registration.target, HookTarget::Static(TEXT.as_ptr() as usize + 0x8));
+
+    round_trip!({
+        mock::set_main_text(&TEXT);
+        install_hook!(inline_hook)
+    }, uninstall_hook!(inline_hook), Registration::InlineHook(_));
+}
+
+#[test]
+fn acmd_scripts() {
+    let registrations = round_trip!(install_acmd_script!(mario_jab), uninstall_acmd_script!(mario_jab), Registration::AcmdScript(_));
+    assert_eq!(registrations[0].script, Hash40::new("game_attack11"));
+}
+
+#[test]
+fn status_scripts() {
+    round_trip!(install_status_script!(mario_attack_main), uninstall_status_script!(mario_attack_main), Registration::StatusScript(_));
+    round_trip!(install_status_script!(common_attack_pre), uninstall_status_script!(common_attack_pre), Registration::CommonStatusScript(_));
+    round_trip!(install_status_script!(mario_attack_check_attack), uninstall_status_script!(mario_attack_check_attack), Registration::StatusScript(_));
+}
+
+#[test]
+fn status_scripts_are_removed_by_status_and_condition() {
+    mock::reset();
+    let mario = Hash40::new("mario");
+    let replacement = mario_attack_main as *const extern "C" fn();
+    unsafe {
+        let main = replace_status_script(mario, LuaConstant::Evaluated(1), StatusCondition::Main.into(), None, false, replacement).unwrap();
+        replace_status_script(mario, LuaConstant::Evaluated(1), StatusCondition::End.into(), None, false, replacement).unwrap();
+        replace_status_script(mario, LuaConstant::Evaluated(2), StatusCondition::End.into(), None, false, replacement).unwrap();
+
+        remove_status_script(mario, LuaConstant::Evaluated(1), StatusCondition::End.into(), replacement);
+        remove_status_script(mario, LuaConstant::Evaluated(2), StatusCondition::End.into(), replacement);
+        mock::with_registrations(|registrations| {
+            assert_eq!(registrations.len(), 1);
+            assert_eq!(registrations[0].id(), main.id);
+        });
+    }
+}
+
+#[test]
+fn agent_frames() {
+    round_trip!(install_agent_frame!(mario_frame), uninstall_agent_frame!(mario_frame), Registration::FighterFrame(_));
+    round_trip!(install_agent_frame!(fireball_frame), uninstall_agent_frame!(fireball_frame), Registration::WeaponFrame(_));
+}
+
+#[test]
+fn callbacks() {
+    round_trip!(install_agent_reset!(fighter_reset), uninstall_agent_reset!(fighter_reset), Registration::FighterReset(_));
+    round_trip!(install_agent_reset!(agent_reset), uninstall_agent_reset!(agent_reset), Registration::AgentReset(_));
+    round_trip!(
+        install_agent_frame_callback!(fighter_frame_callback),
+        uninstall_agent_frame_callback!(fighter_frame_callback),
+        Registration::FighterFrameCallback(_)
+    );
+    round_trip!(
+        install_agent_frame_callback!(weapon_frame_callback),
+        uninstall_agent_frame_callback!(weapon_frame_callback),
+        Registration::WeaponFrameCallback(_)
+    );
+    round_trip!(install_agent_init_callback!(fighter_init), uninstall_agent_init_callback!(fighter_init), Registration::FighterInit(_));
+    round_trip!(install_agent_init_callback!(agent_init), uninstall_agent_init_callback!(agent_init), Registration::AgentInit(_));
+}
+
+#[test]
+fn reset_clears_every_table() {
+    mock::reset();
+    mock::set_plugin("other");
+    let registration = install_acmd_script!(mario_jab).unwrap().remove(0);
+    unsafe { reserve_status_kind(Hash40::new("mario"), "MARIO_CUSTOM"); }
+
+    mock::reset();
+    assert!(mock::take_registrations().is_empty());
+    assert_eq!(registration.state(), None);
+    assert!(registry::all().is_empty());
+    assert_eq!(unsafe { get_status_kind(Hash40::new("mario"), "MARIO_CUSTOM") }, None);
+    assert_eq!(install_acmd_script!(mario_jab).unwrap()[0].id, RegistrationId(0));
+    assert_eq!(registry::all()[0].plugin, "mock");
+    uninstall_acmd_script!(mario_jab);
+}
+
+#[test]
+fn missing_runtime() {
+    mock::reset();
+    let registration = install_acmd_script!(mario_jab).unwrap().remove(0);
+
+    mock::set_runtime_loaded(false);
+    assert!(!is_runtime_available());
+    assert_eq!(install_status_script!(mario_attack_main).unwrap_err(), InstallError::RuntimeMissing);
+    assert_eq!(install_agent_init_callback!(agent_init).unwrap_err(), InstallError::RuntimeMissing);
+    assert!(registry::all().is_empty());
+    // uninstalling doesn't call into a runtime that isn't there
+    uninstall_acmd_script!(mario_jab);
+
+    mock::set_runtime_loaded(true);
+    mock::with_registrations(|registrations| {
+        assert_eq!(registrations.len(), 1);
+        assert_eq!(registrations[0].id(), registration.id);
+    });
+    uninstall_acmd_script!(mario_jab);
+}
+
+static mut INSTALLER_RAN: bool = false;
+
+#[installer]
+fn install_plugin() {
+    unsafe { INSTALLER_RAN = true; }
+}
+
+#[test]
+fn abi_mismatch() {
+    mock::reset();
+    mock::set_runtime_abi_version(None);
+    assert_eq!(check_abi_version(), Err(InstallError::AbiMismatch { expected: ABI_VERSION, found: None }));
+    assert_eq!(install_acmd_script!(mario_jab).unwrap_err(), InstallError::AbiMismatch { expected: ABI_VERSION, found: None });
+    install_plugin();
+    assert!(!unsafe { INSTALLER_RAN });
+
+    mock::set_runtime_abi_version(Some(ABI_VERSION + 1));
+    assert_eq!(check_abi_version(), Err(InstallError::AbiMismatch { expected: ABI_VERSION, found: Some(ABI_VERSION + 1) }));
+    install_plugin();
+    assert!(!unsafe { INSTALLER_RAN });
+
+    mock::set_runtime_abi_version(Some(ABI_VERSION));
+    install_plugin();
+    assert!(unsafe { INSTALLER_RAN });
+    mock::with_registrations(|registrations| assert!(registrations.is_empty()));
+}
+
+#[status_script(agent = "mario", status = custom("MARIO_SPECIAL_N_CHARGE"), condition = Main, manual)]
+unsafe fn mario_special_n_charge(fighter: &mut L2CFighterCommon) -> L2CValue {
+    original!(fighter)
+}
+
+#[test]
+fn custom_status_kinds() {
+    mock::reset();
+    let mario = Hash40::new("mario");
+
+    // uninstalling looks the kind up instead of reserving one
+    uninstall_status_script!(mario_special_n_charge);
+    assert_eq!(unsafe { get_status_kind(mario, "MARIO_SPECIAL_N_CHARGE") }, None);
+
+    let registrations = install_status_script!(mario_special_n_charge).unwrap();
+    assert_eq!(registrations[0].status, mock::CUSTOM_STATUS_KIND_START);
+    uninstall_status_script!(mario_special_n_charge);
+    mock::with_registrations(|registrations| assert!(registrations.is_empty()));
+    assert_eq!(unsafe { reserve_status_kind(mario, "MARIO_SPECIAL_N_SHOOT") }, mock::CUSTOM_STATUS_KIND_START + 1);
+}
+
+#[test]
+fn hook_originals() {
+    struct Local(u64);
+
+    // declared in a function body, where a generated module couldn't see `Local`
+    #[hook(module = "common", symbol = "_ZN7lua2cpp16L2CFighterCommon10sub_localEv", manual)]
+    unsafe fn local_hook(local: &Local) -> u64 {
+        original!()(local) + 1
+    }
+
+    extern "C" fn game_function(local: &Local) -> u64 {
+        local.0
+    }
+
+    mock::reset();
+    assert!(local_hook_original_fn().is_none());
+    install_hook!(local_hook).unwrap();
+    unsafe { local_hook_smashline_hook_orig = Some(game_function); }
+    assert_eq!(local_hook_original(&Local(3)), 3);
+    assert_eq!(unsafe { local_hook(&Local(3)) }, 4);
+    uninstall_hook!(local_hook);
+    assert!(local_hook_original_fn().is_none());
+}
+
+#[hook(module = "common", symbol = "_ZN7lua2cpp16L2CFighterCommon10sub_orderEv", manual)]
+unsafe fn ordered_first(x: u64) -> u64 {
+    original!()(x) + 1
+}
+
+#[hook(module = "common", symbol = "_ZN7lua2cpp16L2CFighterCommon10sub_orderEv", before = "first", manual)]
+unsafe fn ordered_second(x: u64) -> u64 {
+    original!()(x) * 2
+}
+
+#[test]
+fn hook_order_names_plugins_like_the_registry() {
+    mock::reset();
+    mock::set_plugin("first");
+    install_hook!(ordered_first).unwrap();
+    mock::set_plugin("second");
+    install_hook!(ordered_second).unwrap();
+
+    let plugins: Vec<String> = registry::hooks().into_iter().map(|entry| entry.plugin).collect();
+    assert_eq!(plugins, vec!["first", "second"]);
+    // installed last but placed before "first", so it calls into it
+    assert_eq!(ordered_second_original_fn().map(|original| original as usize), Some(ordered_first as usize));
+    assert!(ordered_first_original_fn().is_none());
+    uninstall_hooks!(ordered_first, ordered_second);
+}
+
+#[cfg(feature = "install_all")]
+#[acmd_script(agent = "luigi", script = "game_attack11", category = ACMD_GAME)]
+fn luigi_jab(_fighter: &mut L2CAgentBase) {}
+
+#[cfg(feature = "install_all")]
+fn never() -> bool {
+    false
+}
+
+#[cfg(feature = "install_all")]
+#[acmd_script(agent = "luigi", script = "game_attack12", category = ACMD_GAME, enabled_if(never))]
+fn luigi_jab_disabled(_fighter: &mut L2CAgentBase) {}
+
+#[cfg(feature = "install_all")]
+#[test]
+fn install_all_skips_installed_items() {
+    mock::reset();
+    install_acmd_script!(luigi_jab).unwrap();
+    assert!(install_all().unwrap().is_empty());
+    mock::with_registrations(|registrations| assert_eq!(registrations.len(), 1));
+
+    uninstall_all();
+    mock::with_registrations(|registrations| assert!(registrations.is_empty()));
+    assert!(install_all().unwrap().is_empty());
+    mock::with_registrations(|registrations| assert_eq!(registrations.len(), 1));
+    uninstall_all();
+}
+
+#[acmd_script(agent = "peach", script = "game_attack11", category = ACMD_GAME, manual)]
+fn peach_jab_a(_fighter: &mut L2CAgentBase) {}
+
+#[acmd_script(agent = "peach", script = "game_attack11", category = ACMD_GAME, manual)]
+fn peach_jab_b(_fighter: &mut L2CAgentBase) {}
+
+#[acmd_script(agent = "peach", script = "game_attack11", category = ACMD_GAME, low_priority, manual)]
+fn peach_jab_low(_fighter: &mut L2CAgentBase) {}
+
+// (told plugin, existing plugin, incoming plugin, policy)
+type Told = (&'static str, String, String, ConflictPolicy);
+
+thread_local! {
+    static CONFLICTS: std::cell::RefCell<Vec<Told>> = std::cell::RefCell::new(Vec::new());
+}
+
+fn tell(plugin: &'static str, conflict: &Conflict) {
+    CONFLICTS.with(|conflicts| conflicts.borrow_mut().push((plugin, conflict.existing.plugin.clone(), conflict.incoming_plugin.clone(), conflict.policy)));
+}
+
+fn tell_first(conflict: &Conflict) {
+    tell("first", conflict);
+}
+
+fn tell_second(conflict: &Conflict) {
+    tell("second", conflict);
+}
+
+fn take_conflicts() -> Vec<Told> {
+    CONFLICTS.with(|conflicts| conflicts.borrow_mut().drain(..).collect())
+}
+
+fn told(plugin: &'static str, existing: &str, incoming: &str, policy: ConflictPolicy) -> Told {
+    (plugin, existing.to_owned(), incoming.to_owned(), policy)
+}
+
+#[test]
+fn conflicts() {
+    mock::reset();
+
+    mock::set_plugin("first");
+    set_conflict_resolution(ConflictPolicy::Report, tell_first);
+    let a = install_acmd_script!(peach_jab_a).unwrap();
+    install_acmd_script!(peach_jab_low).unwrap();
+    assert!(take_conflicts().is_empty());
+
+    // another plugin's registration is only reported, to both plugins
+    mock::set_plugin("second");
+    set_conflict_resolution(ConflictPolicy::ReplaceExisting, tell_second);
+    install_acmd_script!(peach_jab_b).unwrap();
+    assert_eq!(take_conflicts(), vec![
+        told("second", "first", "second", ConflictPolicy::Report),
+        told("first", "first", "second", ConflictPolicy::Report)
+    ]);
+    assert_eq!(a[0].state(), Some(RegistrationState::Active));
+    uninstall_acmd_script!(peach_jab_b);
+
+    // the new registration is never made
+    set_conflict_resolution(ConflictPolicy::KeepExisting, tell_second);
+    assert_eq!(install_acmd_script!(peach_jab_b).unwrap_err(), InstallError::Collision);
+    assert_eq!(take_conflicts(), vec![
+        told("second", "first", "second", ConflictPolicy::KeepExisting),
+        told("first", "first", "second", ConflictPolicy::KeepExisting)
+    ]);
+    assert_eq!(registry::acmd_for(Hash40::new("peach")).len(), 2);
+
+    // the plugin's own registration is disabled before the new one is made, and the plugin is told once
+    mock::set_plugin("first");
+    set_conflict_resolution(ConflictPolicy::ReplaceExisting, tell_first);
+    let b = install_acmd_script!(peach_jab_b).unwrap();
+    assert_eq!(take_conflicts(), vec![told("first", "first", "first", ConflictPolicy::ReplaceExisting)]);
+    assert_eq!(a[0].state(), Some(RegistrationState::Disabled));
+    assert_eq!(b[0].state(), Some(RegistrationState::Active));
+
+    uninstall_acmd_scripts!(peach_jab_a, peach_jab_b, peach_jab_low);
+}
+
+#[acmd_script(agent = "peach", scripts = ["game_attack12", "game_attack13"], category = ACMD_GAME, manual)]
+fn peach_jabs(_fighter: &mut L2CAgentBase) {}
+
+#[acmd_script(agent = "peach", script = "game_attack13", category = ACMD_GAME, manual)]
+fn peach_jab_3(_fighter: &mut L2CAgentBase) {}
+
+#[status_script(agent = "peach", statuses = [FIGHTER_STATUS_KIND_SPECIAL_N, FIGHTER_STATUS_KIND_SPECIAL_S], condition = Main, manual)]
+unsafe fn peach_specials(fighter: &mut L2CFighterCommon) -> L2CValue {
+    original!(fighter)
+}
+
+#[status_script(agent = "peach", status = FIGHTER_STATUS_KIND_SPECIAL_S, condition = Main, manual)]
+unsafe fn peach_special_s(fighter: &mut L2CFighterCommon) -> L2CValue {
+    original!(fighter)
+}
+
+#[test]
+fn failed_installs_are_rolled_back() {
+    mock::reset();
+    install_acmd_script!(peach_jab_3).unwrap();
+    install_status_script!(peach_special_s).unwrap();
+
+    // the first script or status of each item is registered before the second one collides
+    set_conflict_resolution(ConflictPolicy::KeepExisting, |_| {});
+    assert_eq!(install_acmd_script!(peach_jabs).unwrap_err(), InstallError::Collision);
+    assert_eq!(install_status_script!(peach_specials).unwrap_err(), InstallError::Collision);
+    mock::with_registrations(|registrations| assert_eq!(registrations.len(), 2));
+    assert_eq!(registry::acmd_for(Hash40::new("peach")).len(), 1);
+    assert_eq!(registry::status_for(Hash40::new("peach")).len(), 1);
+
+    uninstall_acmd_script!(peach_jab_3);
+    uninstall_status_script!(peach_special_s);
+}